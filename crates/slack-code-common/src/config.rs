@@ -1,25 +1,37 @@
 use crate::error::{Result, SlackCodeError};
+use crate::ipc::PermissionDecision;
+use crate::session::{SessionStatus, StatusKind};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Main configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
     pub slack: SlackConfig,
     #[serde(default)]
     pub daemon: DaemonConfig,
     #[serde(default)]
     pub defaults: DefaultsConfig,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            slack: SlackConfig::default(),
-            daemon: DaemonConfig::default(),
-            defaults: DefaultsConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub interaction: InteractionConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub transcript: TranscriptConfig,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    #[serde(default)]
+    pub notifiers: NotifiersConfig,
+    /// Channels for specific repositories; unmatched repos go to your DMs
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+    /// Repository paths by alias, for starting sessions from Slack
+    #[serde(default)]
+    pub repos: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    pub launch: LaunchConfig,
 }
 
 /// Slack API configuration
@@ -37,6 +49,11 @@ pub struct SlackConfig {
 
     /// Your Slack Member ID (required, set during setup)
     pub user_id: String,
+
+    /// Slack Web API base URL, for pointing the daemon at a stand-in
+    /// (defaults to https://slack.com/api)
+    #[serde(default)]
+    pub api_url: Option<String>,
 }
 
 impl SlackConfig {
@@ -79,6 +96,10 @@ pub struct DaemonConfig {
     /// Log file path
     #[serde(default = "default_log_file")]
     pub log_file: PathBuf,
+
+    /// Slack messages not yet delivered, kept across restarts
+    #[serde(default = "default_outbox_file")]
+    pub outbox_file: PathBuf,
 }
 
 impl Default for DaemonConfig {
@@ -88,6 +109,7 @@ impl Default for DaemonConfig {
             pid_file: default_pid_file(),
             log_level: default_log_level(),
             log_file: default_log_file(),
+            outbox_file: default_outbox_file(),
         }
     }
 }
@@ -108,6 +130,519 @@ impl Default for DefaultsConfig {
     }
 }
 
+/// Driving sessions from Slack (requires Socket Mode)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionConfig {
+    /// When Claude stops, wait for a reply in the session thread
+    /// and send it to Claude as the next prompt
+    #[serde(default)]
+    pub thread_replies: bool,
+
+    /// How long the Stop hook waits for a thread reply (seconds)
+    #[serde(default = "default_reply_timeout")]
+    pub reply_timeout: u64,
+
+    /// Post Approve/Deny buttons in the thread for permission prompts
+    #[serde(default)]
+    pub permission_buttons: bool,
+
+    /// How long to wait for a button press (seconds)
+    #[serde(default = "default_permission_timeout")]
+    pub permission_timeout: u64,
+
+    /// Decision to use when nobody answers in time (allow, deny, ask)
+    #[serde(default)]
+    pub permission_fallback: PermissionDecision,
+
+    /// How long Claude Code gets to exit after SIGINT before it is sent
+    /// SIGTERM, when a session is stopped (seconds)
+    #[serde(default = "default_stop_grace")]
+    pub stop_grace: u64,
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            thread_replies: false,
+            reply_timeout: default_reply_timeout(),
+            permission_buttons: false,
+            permission_timeout: default_permission_timeout(),
+            permission_fallback: PermissionDecision::default(),
+            stop_grace: default_stop_grace(),
+        }
+    }
+}
+
+/// How status changes show up in Slack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Edit the session's root message in place (chat.update) with the
+    /// current status, duration and last event
+    #[serde(default)]
+    pub update_root: bool,
+
+    /// Statuses that still get a thread reply mentioning you when
+    /// update_root is on
+    #[serde(default = "default_thread_reply_statuses")]
+    pub thread_reply_statuses: Vec<StatusKind>,
+
+    /// Keep an emoji reaction on the root message showing the current status
+    #[serde(default = "default_status_reactions")]
+    pub status_reactions: bool,
+
+    /// Hours during which Slack stays quiet
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHoursConfig>,
+
+    /// Seconds to hold a waiting-for-input reply before posting it, dropped
+    /// if Claude is back at work by then (0 = post right away)
+    #[serde(default)]
+    pub settle_seconds: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            update_root: false,
+            thread_reply_statuses: default_thread_reply_statuses(),
+            status_reactions: default_status_reactions(),
+            quiet_hours: None,
+            settle_seconds: 0,
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Whether a change to this status should be posted as a thread reply
+    pub fn wants_thread_reply(&self, status: &SessionStatus) -> bool {
+        if self.update_root {
+            self.thread_reply_statuses.contains(&status.kind())
+        } else {
+            // Every change except Completed gets a reply
+            !matches!(status, SessionStatus::Completed)
+        }
+    }
+}
+
+/// A daily window without Slack notifications, e.g. overnight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHoursConfig {
+    /// Local time the window starts ("22:00")
+    pub start: String,
+
+    /// Local time the window ends ("07:30"); may be earlier than start
+    pub end: String,
+
+    /// IANA time zone such as "Europe/Berlin" (defaults to the system's)
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// What happens to notifications during the window
+    #[serde(default)]
+    pub mode: QuietMode,
+}
+
+/// How notifications are handled during quiet hours
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietMode {
+    /// Hold back new messages until the window ends
+    #[default]
+    Hold,
+    /// Post as usual, but without mentioning you
+    Silent,
+}
+
+impl QuietHoursConfig {
+    /// Check the times and time zone
+    pub fn validate(&self) -> Result<()> {
+        parse_clock_time(&self.start)?;
+        parse_clock_time(&self.end)?;
+        self.time_zone()?;
+        Ok(())
+    }
+
+    fn time_zone(&self) -> Result<jiff::tz::TimeZone> {
+        match self.timezone {
+            Some(ref name) => jiff::tz::TimeZone::get(name).map_err(|e| {
+                SlackCodeError::Config(format!("Unknown time zone '{}': {}", name, e))
+            }),
+            None => Ok(jiff::tz::TimeZone::system()),
+        }
+    }
+
+    /// When the quiet window around `now` ends, or `None` outside quiet hours
+    pub fn quiet_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = parse_clock_time(&self.start).ok()?;
+        let end = parse_clock_time(&self.end).ok()?;
+        let tz = self.time_zone().ok()?;
+        let local = jiff::Timestamp::from_second(now.timestamp()).ok()?.to_zoned(tz.clone());
+        let time = local.time();
+
+        let end_date = if start < end {
+            // Same-day window
+            if time < start || time >= end {
+                return None;
+            }
+            local.date()
+        } else if start > end {
+            // Overnight window
+            if time >= start {
+                local.date().tomorrow().ok()?
+            } else if time < end {
+                local.date()
+            } else {
+                return None;
+            }
+        } else {
+            return None;
+        };
+
+        let end_at = end_date.to_datetime(end).to_zoned(tz).ok()?;
+        DateTime::from_timestamp(end_at.timestamp().as_second(), 0)
+    }
+}
+
+/// Parse "HH:MM"
+fn parse_clock_time(text: &str) -> Result<jiff::civil::Time> {
+    jiff::civil::Time::strptime("%H:%M", text).map_err(|_| {
+        SlackCodeError::Config(format!("Invalid time '{}', expected HH:MM", text))
+    })
+}
+
+/// Archiving session transcripts in Slack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptConfig {
+    /// Upload the transcript as a Markdown file in the session thread when
+    /// the session ends
+    #[serde(default)]
+    pub upload: bool,
+
+    /// Largest transcript uploaded, in KB; longer ones are cut off
+    #[serde(default = "default_transcript_max_kb")]
+    pub max_kb: usize,
+}
+
+impl Default for TranscriptConfig {
+    fn default() -> Self {
+        Self {
+            upload: false,
+            max_kb: default_transcript_max_kb(),
+        }
+    }
+}
+
+/// Session lifecycle events a notification backend can be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    /// A session was seen for the first time, or resumed after it ended
+    SessionStart,
+    /// A running session changed status or logged a new event
+    StatusChange,
+    /// A session completed or failed
+    SessionEnd,
+}
+
+/// Backends sessions are reported to; several can be enabled at once
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotifiersConfig {
+    #[serde(default)]
+    pub slack: SlackNotifierConfig,
+
+    #[serde(default)]
+    pub webhook: Vec<WebhookConfig>,
+
+    #[serde(default)]
+    pub exec: Vec<ExecConfig>,
+}
+
+/// The Slack threads, with their own event filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackNotifierConfig {
+    #[serde(default = "default_notifier_enabled")]
+    pub enabled: bool,
+
+    /// Events to post about; `session_start` is needed for the thread
+    #[serde(default = "default_notify_events")]
+    pub events: Vec<NotifyEvent>,
+}
+
+impl Default for SlackNotifierConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            events: default_notify_events(),
+        }
+    }
+}
+
+/// A URL that is sent each session event as a JSON POST
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// Sign payloads with HMAC-SHA256 using this secret
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    #[serde(default = "default_notify_events")]
+    pub events: Vec<NotifyEvent>,
+
+    /// Further attempts after a failed delivery
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+/// A program run for each session event, with the event as JSON on stdin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecConfig {
+    /// Program and its arguments
+    pub command: Vec<String>,
+
+    #[serde(default = "default_notify_events")]
+    pub events: Vec<NotifyEvent>,
+
+    /// Seconds before a run is killed
+    #[serde(default = "default_exec_timeout")]
+    pub timeout: u64,
+
+    /// Runs allowed at once; later events wait their turn
+    #[serde(default = "default_exec_concurrency")]
+    pub concurrency: usize,
+}
+
+/// Custom wording for Slack messages, keyed by `session_start` or a status name;
+/// unset templates keep the built-in text.
+/// Templates are Slack mrkdwn with `{placeholder}`s, and `{{`/`}}` for literal braces.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct TemplatesConfig(pub BTreeMap<String, String>);
+
+/// Messages that can be templated: the first message of a session thread,
+/// then the thread reply for each status
+pub const TEMPLATE_NAMES: &[&str] = &[
+    "session_start",
+    "starting",
+    "running",
+    "permission_prompt",
+    "stopped",
+    "plan_approval",
+    "completed",
+    "failed",
+];
+
+/// Placeholders a template may use
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "repo", "branch", "duration", "host", "message", "prompt", "status",
+];
+
+/// A piece of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart<'a> {
+    Text(String),
+    Placeholder(&'a str),
+}
+
+impl TemplatesConfig {
+    /// Template for the first message of a session thread, if one is set
+    pub fn session_start(&self) -> Option<&str> {
+        self.0.get("session_start").map(String::as_str)
+    }
+
+    /// Template for a thread reply announcing this status, if one is set.
+    /// `stopped` also covers a Stop hook waiting for a thread reply.
+    pub fn for_status(&self, status: &SessionStatus) -> Option<&str> {
+        let name = match status.kind() {
+            StatusKind::Starting => "starting",
+            StatusKind::Running => "running",
+            StatusKind::PermissionPrompt => "permission_prompt",
+            StatusKind::Stopped => "stopped",
+            StatusKind::PlanApproval => "plan_approval",
+            StatusKind::Completed => "completed",
+            StatusKind::Failed => "failed",
+        };
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Check template names, placeholders and braces
+    pub fn validate(&self) -> Result<()> {
+        for (name, template) in &self.0 {
+            if !TEMPLATE_NAMES.contains(&name.as_str()) {
+                return Err(SlackCodeError::Config(format!(
+                    "templates.{}: unknown template, expected one of {}",
+                    name,
+                    TEMPLATE_NAMES.join(", ")
+                )));
+            }
+            parse_template(template).map_err(|e| match e {
+                SlackCodeError::Config(message) => {
+                    SlackCodeError::Config(format!("templates.{}: {}", name, message))
+                }
+                other => other,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a template into text and known placeholders
+pub fn parse_template(template: &str) -> Result<Vec<TemplatePart<'_>>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        text.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            text.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if tail.starts_with('}') {
+            return Err(SlackCodeError::Config(
+                "unmatched '}' (write '}}' for a literal brace)".to_string(),
+            ));
+        } else {
+            let end = tail.find('}').ok_or_else(|| {
+                SlackCodeError::Config(
+                    "unclosed '{' (write '{{' for a literal brace)".to_string(),
+                )
+            })?;
+            let name = &tail[1..end];
+            if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+                let known: Vec<String> = TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect();
+                return Err(SlackCodeError::Config(format!(
+                    "unknown placeholder {{{}}}, expected one of {}",
+                    name,
+                    known.join(", ")
+                )));
+            }
+            if !text.is_empty() {
+                parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+            }
+            parts.push(TemplatePart::Placeholder(name));
+            rest = &tail[end + 1..];
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    Ok(parts)
+}
+
+/// Fill in a template's placeholders; an invalid template is used as written
+pub fn render_template(template: &str, value: impl Fn(&str) -> String) -> String {
+    match parse_template(template) {
+        Ok(parts) => parts
+            .into_iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text,
+                TemplatePart::Placeholder(name) => value(name),
+            })
+            .collect(),
+        Err(_) => template.to_string(),
+    }
+}
+
+/// Starting Claude Code sessions from Slack with `/claude run`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchConfig {
+    /// Command that runs Claude Code headless; the prompt is added as the
+    /// last argument
+    #[serde(default = "default_launch_command")]
+    pub command: Vec<String>,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        Self {
+            command: default_launch_command(),
+        }
+    }
+}
+
+/// Send a repository's sessions to a channel instead of your DMs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteConfig {
+    /// Glob matched against the repository path (`*` stays within a
+    /// directory, `**` crosses directories, `~/` is your home)
+    #[serde(default)]
+    pub path: Option<String>,
+
+    /// Repository alias the session was started with
+    #[serde(default)]
+    pub alias: Option<String>,
+
+    /// Slack channel ID to post to (C...)
+    pub channel: String,
+}
+
+impl RouteConfig {
+    /// Whether a session in this repository belongs to the route
+    pub fn matches(&self, repo_path: &Path, alias: Option<&str>) -> bool {
+        let alias_matches = self
+            .alias
+            .as_deref()
+            .is_some_and(|a| alias == Some(a));
+        let path_matches = self.path.as_deref().is_some_and(|pattern| {
+            let pattern = match (pattern.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+                _ => pattern.to_string(),
+            };
+            glob_match(
+                pattern.trim_end_matches('/'),
+                repo_path.to_string_lossy().trim_end_matches('/'),
+            )
+        });
+
+        alias_matches || path_matches
+    }
+}
+
+/// Channel for a repository's sessions: the first matching route, if any
+pub fn route_channel<'a>(
+    routes: &'a [RouteConfig],
+    repo_path: &Path,
+    alias: Option<&str>,
+) -> Option<&'a str> {
+    routes
+        .iter()
+        .find(|r| r.matches(repo_path, alias))
+        .map(|r| r.channel.as_str())
+}
+
+/// Match a path against a glob with `*`, `**` and `?`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => match pattern[2..].strip_prefix(&['/']) {
+            // `**/` matches zero or more whole directories
+            Some(rest) => (0..=text.len())
+                .filter(|&i| i == 0 || text[i - 1] == '/')
+                .any(|i| glob_match_from(rest, &text[i..])),
+            // Otherwise `**` matches anything, including separators
+            None => (0..=text.len()).any(|i| glob_match_from(&pattern[2..], &text[i..])),
+        },
+        Some('*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_match_from(&pattern[1..], &text[i..])),
+        Some('?') => {
+            text.first().is_some_and(|&c| c != '/') && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some(&c) => text.first() == Some(&c) && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
+
 // Default value functions
 fn default_socket_path() -> PathBuf {
     get_runtime_dir().join("slack-code/daemon.sock")
@@ -125,10 +660,71 @@ fn default_log_file() -> PathBuf {
     get_data_dir().join("slack-code/daemon.log")
 }
 
+fn default_outbox_file() -> PathBuf {
+    get_data_dir().join("slack-code/outbox.json")
+}
+
 fn default_hook_timeout() -> u64 {
     5
 }
 
+fn default_reply_timeout() -> u64 {
+    600
+}
+
+fn default_permission_timeout() -> u64 {
+    300
+}
+
+fn default_stop_grace() -> u64 {
+    10
+}
+
+fn default_status_reactions() -> bool {
+    true
+}
+
+fn default_notifier_enabled() -> bool {
+    true
+}
+
+fn default_notify_events() -> Vec<NotifyEvent> {
+    vec![
+        NotifyEvent::SessionStart,
+        NotifyEvent::StatusChange,
+        NotifyEvent::SessionEnd,
+    ]
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+fn default_exec_timeout() -> u64 {
+    10
+}
+
+fn default_exec_concurrency() -> usize {
+    2
+}
+
+fn default_transcript_max_kb() -> usize {
+    512
+}
+
+fn default_launch_command() -> Vec<String> {
+    vec!["claude".to_string(), "-p".to_string()]
+}
+
+fn default_thread_reply_statuses() -> Vec<StatusKind> {
+    vec![
+        StatusKind::PermissionPrompt,
+        StatusKind::Stopped,
+        StatusKind::PlanApproval,
+        StatusKind::Failed,
+    ]
+}
+
 // Directory helpers
 pub fn get_config_dir() -> PathBuf {
     dirs::config_dir()
@@ -161,9 +757,72 @@ impl Config {
 
         let content = std::fs::read_to_string(&config_path)?;
         let config: Config = toml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Path of the repository with this alias, with `~/` expanded
+    pub fn repo_path(&self, alias: &str) -> Option<PathBuf> {
+        self.repos.get(alias).map(|path| expand_home(path))
+    }
+
+    /// Every repository alias with its path, `~/` expanded
+    pub fn repo_paths(&self) -> Vec<(String, PathBuf)> {
+        self.repos
+            .iter()
+            .map(|(alias, path)| (alias.clone(), expand_home(path)))
+            .collect()
+    }
+
+    /// Check settings that parse but can't be used
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref quiet_hours) = self.notifications.quiet_hours {
+            quiet_hours.validate().map_err(|e| match e {
+                SlackCodeError::Config(message) => {
+                    SlackCodeError::Config(format!("notifications.quiet_hours: {}", message))
+                }
+                other => other,
+            })?;
+        }
+        self.templates.validate()?;
+        for alias in self.repos.keys() {
+            validate_repo_alias(alias)?;
+        }
+        let slack = &self.notifiers.slack;
+        if slack.enabled && !slack.events.contains(&NotifyEvent::SessionStart) {
+            return Err(SlackCodeError::Config(
+                "notifiers.slack.events: needs session_start, which creates the thread"
+                    .to_string(),
+            ));
+        }
+        for webhook in &self.notifiers.webhook {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                return Err(SlackCodeError::Config(format!(
+                    "notifiers.webhook: {} is not an http:// or https:// URL",
+                    webhook.url
+                )));
+            }
+        }
+        for exec in &self.notifiers.exec {
+            if exec.command.is_empty() {
+                return Err(SlackCodeError::Config(
+                    "notifiers.exec.command: needs at least the program to run".to_string(),
+                ));
+            }
+            if exec.concurrency == 0 {
+                return Err(SlackCodeError::Config(
+                    "notifiers.exec.concurrency: must be at least 1".to_string(),
+                ));
+            }
+        }
+        if self.launch.command.is_empty() {
+            return Err(SlackCodeError::Config(
+                "launch.command: needs at least the program to run".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path();
@@ -184,9 +843,38 @@ impl Config {
     }
 }
 
+/// Check that an alias can be typed after `/claude run`
+pub fn validate_repo_alias(alias: &str) -> Result<()> {
+    if alias.is_empty() || alias.contains(char::is_whitespace) {
+        return Err(SlackCodeError::Config(format!(
+            "repos: alias '{}' must be one word",
+            alias
+        )));
+    }
+    Ok(())
+}
+
+/// Replace a leading `~` with the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
 // Claude Code hooks management
 const HOOK_COMMAND: &str = "slack-code-hook";
 
+/// Every hook event `install_hooks` sets up
+const HOOK_EVENTS: &[&str] = &[
+    "SessionStart",
+    "SessionEnd",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "PermissionRequest",
+];
+
 /// Install hooks into Claude Code settings
 pub fn install_hooks() -> Result<()> {
     let claude_settings_path = dirs::home_dir()
@@ -236,12 +924,37 @@ pub fn install_hooks() -> Result<()> {
         }]
     }]);
 
+    // Add UserPromptSubmit hook - fires when a new prompt sets Claude back to work
+    hooks["UserPromptSubmit"] = serde_json::json!([{
+        "hooks": [{
+            "type": "command",
+            "command": HOOK_COMMAND,
+            "timeout": 5
+        }]
+    }]);
+
+    // Stop and PermissionRequest hooks may block while waiting for an answer
+    // from Slack, so give them a little longer than the configured timeouts
+    let interaction = Config::load()
+        .map(|c| c.interaction)
+        .unwrap_or_default();
+
     // Add Stop hook - fires when Claude finishes responding
     hooks["Stop"] = serde_json::json!([{
         "hooks": [{
             "type": "command",
             "command": HOOK_COMMAND,
-            "timeout": 5
+            "timeout": interaction.reply_timeout + 30
+        }]
+    }]);
+
+    // Add PermissionRequest hook - fires before a permission dialog is shown
+    hooks["PermissionRequest"] = serde_json::json!([{
+        "matcher": "*",
+        "hooks": [{
+            "type": "command",
+            "command": HOOK_COMMAND,
+            "timeout": interaction.permission_timeout + 30
         }]
     }]);
 
@@ -273,9 +986,9 @@ pub fn uninstall_hooks() -> Result<()> {
     if let Some(hooks) = settings.get_mut("hooks") {
         // Remove our hooks
         if let Some(obj) = hooks.as_object_mut() {
-            obj.remove("SessionStart");
-            obj.remove("SessionEnd");
-            obj.remove("Notification");
+            for event in HOOK_EVENTS {
+                obj.remove(*event);
+            }
         }
     }
 
@@ -285,7 +998,7 @@ pub fn uninstall_hooks() -> Result<()> {
     Ok(())
 }
 
-/// Check if hooks are installed
+/// Check if hooks are installed, including ones added since an older install
 pub fn check_hooks_installed() -> Result<bool> {
     let claude_settings_path = dirs::home_dir()
         .ok_or_else(|| SlackCodeError::Hook("Cannot determine home directory".into()))?
@@ -300,11 +1013,7 @@ pub fn check_hooks_installed() -> Result<bool> {
 
     // Check if our hooks exist
     if let Some(hooks) = settings.get("hooks") {
-        let has_session_start = hooks.get("SessionStart").is_some();
-        let has_session_end = hooks.get("SessionEnd").is_some();
-        let has_notification = hooks.get("Notification").is_some();
-
-        return Ok(has_session_start && has_session_end && has_notification);
+        return Ok(HOOK_EVENTS.iter().all(|event| hooks.get(event).is_some()));
     }
 
     Ok(false)
@@ -322,4 +1031,147 @@ mod tests {
         );
         assert_eq!(SlackConfig::mask_token("short"), "****");
     }
+
+    #[test]
+    fn test_quiet_hours() {
+        let at = |text: &str| text.parse::<DateTime<Utc>>().unwrap();
+        let overnight = QuietHoursConfig {
+            start: "22:00".to_string(),
+            end: "07:30".to_string(),
+            timezone: Some("America/New_York".to_string()),
+            mode: QuietMode::Hold,
+        };
+        overnight.validate().unwrap();
+
+        // 03:00 in New York (EST, UTC-5)
+        assert_eq!(
+            overnight.quiet_until(at("2025-01-15T08:00:00Z")),
+            Some(at("2025-01-15T12:30:00Z"))
+        );
+        // 23:00 the evening before
+        assert_eq!(
+            overnight.quiet_until(at("2025-01-15T04:00:00Z")),
+            Some(at("2025-01-15T12:30:00Z"))
+        );
+        // Noon
+        assert_eq!(overnight.quiet_until(at("2025-01-15T17:00:00Z")), None);
+
+        let daytime = QuietHoursConfig {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+            timezone: Some("UTC".to_string()),
+            mode: QuietMode::Silent,
+        };
+        assert_eq!(
+            daytime.quiet_until(at("2025-01-15T12:15:00Z")),
+            Some(at("2025-01-15T13:00:00Z"))
+        );
+        assert_eq!(daytime.quiet_until(at("2025-01-15T13:00:00Z")), None);
+
+        let invalid = QuietHoursConfig {
+            start: "25:00".to_string(),
+            ..daytime.clone()
+        };
+        assert!(invalid.validate().is_err());
+        let unknown_zone = QuietHoursConfig {
+            timezone: Some("Mars/Olympus".to_string()),
+            ..daytime
+        };
+        assert!(unknown_zone.validate().is_err());
+    }
+
+    #[test]
+    fn test_thread_reply_statuses() {
+        let config: Config = toml::from_str(
+            r#"
+            [slack]
+            user_id = "U12345678"
+
+            [notifications]
+            update_root = true
+            thread_reply_statuses = ["stopped"]
+            "#,
+        )
+        .unwrap();
+
+        let notifications = &config.notifications;
+        assert!(notifications.wants_thread_reply(&SessionStatus::WaitingForInput(
+            crate::WaitReason::Stopped
+        )));
+        assert!(!notifications.wants_thread_reply(&SessionStatus::Running));
+        assert!(!notifications.wants_thread_reply(&SessionStatus::Failed("boom".into())));
+    }
+
+    #[test]
+    fn test_templates() {
+        let config: Config = toml::from_str(
+            r#"
+            [slack]
+            user_id = "U12345678"
+
+            [templates]
+            stopped = "{repo} on {branch} is idle after {duration} {{ok}}"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let template = config
+            .templates
+            .for_status(&SessionStatus::WaitingForInput(crate::WaitReason::Stopped))
+            .unwrap();
+        assert_eq!(
+            render_template(template, |name| name.to_uppercase()),
+            "REPO on BRANCH is idle after DURATION {ok}"
+        );
+        assert_eq!(config.templates.for_status(&SessionStatus::Running), None);
+
+        let unknown = TemplatesConfig(BTreeMap::from([(
+            "failed".to_string(),
+            "{repo} failed: {reason}".to_string(),
+        )]));
+        let error = unknown.validate().unwrap_err().to_string();
+        assert!(error.contains("templates.failed: unknown placeholder {reason}"), "{}", error);
+        let misspelled = TemplatesConfig(BTreeMap::from([(
+            "stoped".to_string(),
+            "{repo}".to_string(),
+        )]));
+        assert!(misspelled.validate().is_err());
+        assert!(parse_template("{repo").is_err());
+        assert!(parse_template("repo}").is_err());
+    }
+
+    #[test]
+    fn test_route_channel() {
+        let config: Config = toml::from_str(
+            r#"
+            [slack]
+            user_id = "U12345678"
+
+            [[routes]]
+            alias = "api"
+            channel = "C0API"
+
+            [[routes]]
+            path = "/work/team/**"
+            channel = "C0TEAM"
+
+            [[routes]]
+            path = "/src/*-service"
+            channel = "C0SVC"
+            "#,
+        )
+        .unwrap();
+
+        let route = |path: &str, alias: Option<&str>| {
+            route_channel(&config.routes, Path::new(path), alias)
+        };
+        assert_eq!(route("/anywhere", Some("api")), Some("C0API"));
+        assert_eq!(route("/work/team/webapp", None), Some("C0TEAM"));
+        assert_eq!(route("/work/team/a/b", None), Some("C0TEAM"));
+        assert_eq!(route("/work/teammate/webapp", None), None);
+        assert_eq!(route("/src/billing-service", None), Some("C0SVC"));
+        assert_eq!(route("/src/nested/billing-service", None), None);
+        assert_eq!(route("/home/dev/webapp", Some("web")), None);
+    }
 }
@@ -1,7 +1,11 @@
 use crate::session::Session;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Environment variable carrying the daemon's session ID to sessions it launched
+pub const SESSION_ID_ENV: &str = "SLACK_CODE_SESSION_ID";
+
 /// Messages sent from Claude Code hooks to the daemon
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HookEvent {
@@ -13,6 +17,15 @@ pub enum HookEvent {
         transcript_path: Option<String>,
         /// Working directory
         cwd: String,
+        /// Our session ID, for sessions the daemon launched (from SLACK_CODE_SESSION_ID)
+        #[serde(default)]
+        launch_id: Option<Uuid>,
+        /// Claude Code's process ID (the hook's parent process)
+        #[serde(default)]
+        pid: Option<u32>,
+        /// When that process started, to tell it apart from one reusing the PID
+        #[serde(default)]
+        pid_started: Option<String>,
     },
 
     /// A session has ended
@@ -31,18 +44,88 @@ pub enum HookEvent {
         notification_type: Option<String>,
     },
 
+    /// Claude wants to use a tool and would show a permission dialog
+    /// (PermissionRequest or PreToolUse hook)
+    PermissionRequest {
+        /// Claude's internal session ID
+        session_id: String,
+        /// Tool Claude wants to run (Bash, Edit, etc.)
+        tool_name: String,
+        /// Tool arguments as sent by Claude Code
+        tool_input: serde_json::Value,
+        /// Seconds to wait for a decision from Slack (None = don't wait)
+        #[serde(default)]
+        decision_timeout: Option<u64>,
+    },
+
     /// Claude finished responding (Stop hook)
     Stop {
         /// Claude's internal session ID
         session_id: String,
+        /// Seconds to wait for a reply in the Slack thread (None = don't wait)
+        #[serde(default)]
+        reply_timeout: Option<u64>,
     },
+
+    /// The user submitted a prompt and Claude is working again
+    UserPromptSubmit {
+        /// Claude's internal session ID
+        session_id: String,
+    },
+}
+
+impl HookEvent {
+    /// How long the hook waits for a `HookResponse`, if it waits at all
+    pub fn response_timeout(&self) -> Option<Duration> {
+        match self {
+            HookEvent::Stop {
+                reply_timeout: Some(secs),
+                ..
+            } => Some(Duration::from_secs(*secs)),
+            // The daemon applies the configured fallback when the decision times out,
+            // so give it a moment before the connection gives up on its own.
+            HookEvent::PermissionRequest {
+                decision_timeout: Some(secs),
+                ..
+            } => Some(Duration::from_secs(*secs + 10)),
+            _ => None,
+        }
+    }
+}
+
+/// Responses sent from daemon to hooks that wait for one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HookResponse {
+    /// Nothing came back from Slack, Claude Code carries on as usual
+    NoReply,
+
+    /// The user replied in the session thread with the next prompt
+    Reply(String),
+
+    /// Decision on a permission request
+    Permission(PermissionDecision),
+}
+
+/// Decision on a tool permission request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionDecision {
+    /// Let the tool run
+    Allow,
+
+    /// Block the tool
+    Deny,
+
+    /// Show the usual permission dialog in the terminal
+    #[default]
+    Ask,
 }
 
 /// Messages sent from daemon to TUI clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DaemonEvent {
     /// A session was created or updated
-    SessionUpdated(Session),
+    SessionUpdated(Box<Session>),
 
     /// A session was removed
     SessionRemoved(Uuid),
@@ -63,7 +146,7 @@ pub enum DaemonEvent {
     SessionList(Vec<Session>),
 
     /// Configuration (response to GetConfig)
-    ConfigResponse(crate::Config),
+    ConfigResponse(Box<crate::Config>),
 }
 
 /// Daemon connection status
@@ -96,6 +179,9 @@ pub enum DaemonCommand {
 
     /// Ping to check if daemon is alive
     Ping,
+
+    /// Interrupt a session's Claude Code process
+    StopSession(Uuid),
 }
 
 /// Response to Ping command
@@ -127,6 +213,11 @@ pub struct ClaudeHookInput {
     /// For SessionEnd events
     #[serde(default)]
     pub reason: Option<String>,
+    /// For PermissionRequest/PreToolUse events
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    #[serde(default)]
+    pub tool_input: Option<serde_json::Value>,
 }
 
 impl ClaudeHookInput {
@@ -137,6 +228,9 @@ impl ClaudeHookInput {
                 session_id: self.session_id.clone(),
                 transcript_path: self.transcript_path.clone(),
                 cwd: self.cwd.clone().unwrap_or_default(),
+                launch_id: None,
+                pid: None,
+                pid_started: None,
             }),
             "SessionEnd" => Some(HookEvent::SessionEnd {
                 session_id: self.session_id.clone(),
@@ -146,8 +240,18 @@ impl ClaudeHookInput {
                 message: self.message.clone().unwrap_or_default(),
                 notification_type: self.notification_type.clone(),
             }),
+            "PermissionRequest" | "PreToolUse" => Some(HookEvent::PermissionRequest {
+                session_id: self.session_id.clone(),
+                tool_name: self.tool_name.clone().unwrap_or_default(),
+                tool_input: self.tool_input.clone().unwrap_or_default(),
+                decision_timeout: None,
+            }),
             "Stop" => Some(HookEvent::Stop {
                 session_id: self.session_id.clone(),
+                reply_timeout: None,
+            }),
+            "UserPromptSubmit" => Some(HookEvent::UserPromptSubmit {
+                session_id: self.session_id.clone(),
             }),
             _ => None,
         }
@@ -1,8 +1,9 @@
 pub mod config;
 pub mod error;
 pub mod ipc;
+pub mod process;
 pub mod session;
 
 pub use config::Config;
 pub use error::SlackCodeError;
-pub use session::{Session, SessionStatus, WaitReason};
+pub use session::{Session, SessionStatus, StatusKind, WaitReason};
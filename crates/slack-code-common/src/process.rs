@@ -0,0 +1,65 @@
+//! Identifying Claude Code's process, so it can be stopped later.
+//!
+//! `ps` is used rather than `/proc` so this works on macOS too.
+
+use std::process::Command;
+
+/// Shells that may sit between Claude Code and the hooks it runs
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "fish"];
+
+/// The process that ran this one, skipping any shell wrapping the hook command
+pub fn claude_pid() -> u32 {
+    let mut pid = std::os::unix::process::parent_id();
+    while let Some((parent, command)) = parent_and_command(pid) {
+        let name = command.rsplit('/').next().unwrap_or(&command);
+        if parent <= 1 || !SHELLS.contains(&name.trim_start_matches('-')) {
+            break;
+        }
+        pid = parent;
+    }
+    pid
+}
+
+/// When the process started, as `ps` reports it. Together with the PID this
+/// tells a process apart from a later one that reuses its PID.
+pub fn start_time(pid: u32) -> Option<String> {
+    ps(pid, "lstart=")
+}
+
+fn parent_and_command(pid: u32) -> Option<(u32, String)> {
+    let output = ps(pid, "ppid=,comm=")?;
+    let (parent, command) = output.split_once(char::is_whitespace)?;
+    Some((parent.parse().ok()?, command.trim().to_string()))
+}
+
+/// One `ps` column for a process, `None` if it isn't running
+fn ps(pid: u32, format: &str) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", format, "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_time() {
+        let pid = std::process::id();
+        let started = start_time(pid).unwrap();
+        assert_eq!(start_time(pid), Some(started));
+        assert_eq!(
+            parent_and_command(pid).unwrap().0,
+            std::os::unix::process::parent_id()
+        );
+
+        // A process that has exited has no start time
+        let mut child = Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+        assert_eq!(start_time(exited), None);
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// A Claude Code session tracked by slack-code
@@ -40,6 +40,26 @@ pub struct Session {
     /// Path to Claude Code's transcript file
     #[serde(default)]
     pub transcript_path: Option<PathBuf>,
+
+    /// Most recent thing that happened in the session
+    #[serde(default)]
+    pub last_event: Option<SessionEvent>,
+
+    /// Git branch checked out in the repository (if any)
+    #[serde(default)]
+    pub git_branch: Option<String>,
+
+    /// Claude's last reply, read from the transcript when it stops
+    #[serde(default)]
+    pub last_message: Option<String>,
+
+    /// Process ID of Claude Code, reported by the SessionStart hook
+    #[serde(default)]
+    pub claude_pid: Option<u32>,
+
+    /// When that process started, so a later process reusing the PID isn't stopped
+    #[serde(default)]
+    pub claude_started: Option<String>,
 }
 
 impl Session {
@@ -56,9 +76,27 @@ impl Session {
             ended_at: None,
             slack_thread: None,
             transcript_path: None,
+            last_event: None,
+            git_branch: None,
+            last_message: None,
+            claude_pid: None,
+            claude_started: None,
         }
     }
 
+    /// Re-read the checked out branch from the repository
+    pub fn refresh_git_branch(&mut self) {
+        self.git_branch = read_git_branch(&self.repo_path);
+    }
+
+    /// Record the most recent event in the session
+    pub fn record_event(&mut self, description: impl Into<String>) {
+        self.last_event = Some(SessionEvent {
+            description: description.into(),
+            at: Utc::now(),
+        });
+    }
+
     /// Get the display name for this session (alias or path)
     pub fn display_name(&self) -> String {
         self.repo_alias
@@ -95,6 +133,28 @@ impl Session {
     }
 }
 
+/// Read the branch name from `.git/HEAD`, following `gitdir:` files used by worktrees.
+/// Returns `None` outside a repository or on a detached HEAD.
+fn read_git_branch(repo_path: &Path) -> Option<String> {
+    let dot_git = repo_path.join(".git");
+    let git_dir = if dot_git.is_file() {
+        let contents = std::fs::read_to_string(&dot_git).ok()?;
+        let target = PathBuf::from(contents.strip_prefix("gitdir:")?.trim());
+        if target.is_absolute() {
+            target
+        } else {
+            repo_path.join(target)
+        }
+    } else {
+        dot_git
+    };
+
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
 /// Current status of a session
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SessionStatus {
@@ -128,6 +188,21 @@ impl SessionStatus {
         }
     }
 
+    /// Get the status without its details
+    pub fn kind(&self) -> StatusKind {
+        match self {
+            SessionStatus::Starting => StatusKind::Starting,
+            SessionStatus::Running => StatusKind::Running,
+            SessionStatus::WaitingForInput(WaitReason::PermissionPrompt) => {
+                StatusKind::PermissionPrompt
+            }
+            SessionStatus::WaitingForInput(WaitReason::Stopped) => StatusKind::Stopped,
+            SessionStatus::WaitingForInput(WaitReason::PlanApproval) => StatusKind::PlanApproval,
+            SessionStatus::Completed => StatusKind::Completed,
+            SessionStatus::Failed(_) => StatusKind::Failed,
+        }
+    }
+
     /// Get a status icon for display
     pub fn icon(&self) -> &str {
         match self {
@@ -140,6 +215,19 @@ impl SessionStatus {
     }
 }
 
+/// Session status without its details, used to pick per-status settings in config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusKind {
+    Starting,
+    Running,
+    PermissionPrompt,
+    Stopped,
+    PlanApproval,
+    Completed,
+    Failed,
+}
+
 /// Reason for waiting on user input
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum WaitReason {
@@ -163,6 +251,16 @@ impl WaitReason {
     }
 }
 
+/// Something that happened in a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEvent {
+    /// Human-readable description
+    pub description: String,
+
+    /// When it happened
+    pub at: DateTime<Utc>,
+}
+
 /// Slack thread information for a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackThread {
@@ -0,0 +1,736 @@
+//! Block Kit layouts for session messages.
+//!
+//! Every builder returns the whole message content: blocks for display, plus a plain
+//! `text` fallback that Slack uses for notifications and clients without Block Kit.
+
+use crate::slack::{escape_mrkdwn, QuietEvent};
+use crate::transcript::markdown_to_mrkdwn;
+use slack_code_common::config::{render_template, TemplatesConfig};
+use slack_code_common::session::{Session, SessionStatus, WaitReason};
+use chrono::{DateTime, Utc};
+use slack_morphism::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Block Kit action IDs for the permission buttons
+pub const PERMISSION_APPROVE_ACTION: &str = "permission_approve";
+pub const PERMISSION_DENY_ACTION: &str = "permission_deny";
+pub const STOP_SESSION_ACTION: &str = "stop_session";
+
+/// Longest prompt shown in the root message (section text is capped at 3000)
+const MAX_PROMPT_CHARS: usize = 2500;
+
+/// Longest reply from Claude quoted when it stops
+const MAX_MESSAGE_CHARS: usize = 2500;
+
+/// Longest part of Claude's reply included in the notification text
+const MAX_PREVIEW_CHARS: usize = 200;
+
+/// Root message of a session thread, mentioning `mention` when set.
+/// With `live_status` the message also carries a colored status section that is
+/// refreshed in place as the session progresses.
+pub fn session_root(
+    session: &Session,
+    host: &str,
+    templates: &TemplatesConfig,
+    mention: Option<&str>,
+    live_status: bool,
+) -> SlackMessageContent {
+    let repo = session.display_name();
+    let (mut text, mut prompt) = match templates.session_start() {
+        Some(template) => {
+            let headline = fill_template(template, session, host);
+            (headline.clone(), headline)
+        }
+        None => (
+            format!(
+                "Claude Code session in {}: {}",
+                escape_mrkdwn(&repo),
+                escape_mrkdwn(&session.prompt)
+            ),
+            format!(
+                "*Prompt:* {}",
+                truncate(&escape_mrkdwn(&session.prompt), MAX_PROMPT_CHARS)
+            ),
+        ),
+    };
+    if let Some(user_id) = mention {
+        text = format!("<@{}> {}", user_id, text);
+        prompt = format!("<@{}> {}", user_id, prompt);
+    }
+
+    let mut context = vec![
+        format!("*Repo:* `{}`", escape_mrkdwn(&repo)),
+        format!("*Host:* {}", escape_mrkdwn(host)),
+    ];
+    if let Some(ref branch) = session.git_branch {
+        context.insert(1, format!("*Branch:* `{}`", escape_mrkdwn(branch)));
+    }
+
+    let mut blocks = vec![
+        SlackBlock::Header(SlackHeaderBlock::new(pt!("Claude Code session"))),
+        SlackBlock::Section(SlackSectionBlock::new().with_text(md!(prompt))),
+        context_block(context),
+    ];
+    if session.is_active() {
+        blocks.push(SlackBlock::Actions(SlackActionsBlock::new(vec![
+            SlackActionBlockElement::Button(
+                SlackBlockButtonElement::new(pt!("Stop agent"))
+                    .with_action_id(STOP_SESSION_ACTION.into())
+                    .with_value(session.id.to_string())
+                    .with_style(SlackBlockButtonStyle::Danger),
+            ),
+        ])));
+    }
+
+    let mut content = SlackMessageContent::new().with_blocks(blocks);
+
+    if live_status {
+        text.push_str(&format!(" ({})", session.status.short_string()));
+
+        let mut status_blocks = vec![SlackBlock::Section(SlackSectionBlock::new().with_text(
+            md!(format!(
+                "{} *{}* · {}",
+                status_emoji(&session.status),
+                session.status.short_string(),
+                session.duration_string()
+            )),
+        ))];
+        if let Some(ref event) = session.last_event {
+            status_blocks.push(context_block(vec![format!(
+                "Last event: {} · <!date^{}^{{time}}|{}>",
+                escape_mrkdwn(&event.description),
+                event.at.timestamp(),
+                event.at.format("%H:%M UTC")
+            )]));
+        }
+        content = content.with_attachments(vec![status_attachment(&session.status, status_blocks)]);
+    }
+
+    content.with_text(text)
+}
+
+/// First line of a status reply: the configured template for the status, or the built-in text
+pub fn status_text(
+    session: &Session,
+    templates: &TemplatesConfig,
+    host: &str,
+    awaiting_reply: bool,
+) -> String {
+    match templates.for_status(&session.status) {
+        Some(template) => fill_template(template, session, host),
+        None => format_status_message(session, awaiting_reply),
+    }
+}
+
+/// Thread reply announcing a status change with `message`, mentioning `mention` when set
+pub fn status_reply(session: &Session, message: String, mention: Option<&str>) -> SlackMessageContent {
+    let mut details = vec![format!(
+        "*Repo:* `{}`",
+        escape_mrkdwn(&session.display_name())
+    )];
+    if let Some(ref branch) = session.git_branch {
+        details.push(format!("*Branch:* `{}`", escape_mrkdwn(branch)));
+    }
+    details.push(format!("*Duration:* {}", session.duration_string()));
+
+    let message = match mention {
+        Some(user_id) => format!("<@{}> {}", user_id, message),
+        None => message,
+    };
+    let mut text = message.clone();
+    let mut blocks = vec![SlackBlock::Section(
+        SlackSectionBlock::new().with_text(md!(message)),
+    )];
+
+    // Say what Claude actually replied with when it stops
+    if let (SessionStatus::WaitingForInput(WaitReason::Stopped), Some(reply)) =
+        (&session.status, &session.last_message)
+    {
+        let preview = reply.split_whitespace().collect::<Vec<_>>().join(" ");
+        text.push_str(&format!(
+            "\n{}",
+            escape_mrkdwn(&truncate(&preview, MAX_PREVIEW_CHARS))
+        ));
+        blocks.push(SlackBlock::Section(SlackSectionBlock::new().with_text(md!(
+            markdown_to_mrkdwn(&truncate(reply, MAX_MESSAGE_CHARS))
+        ))));
+    }
+
+    SlackMessageContent::new()
+        .with_text(text)
+        .with_blocks(blocks)
+        .with_attachments(vec![status_attachment(
+            &session.status,
+            vec![context_block(details)],
+        )])
+}
+
+/// Most status changes listed in the quiet hours summary
+const MAX_CATCH_UP_EVENTS: usize = 30;
+
+/// What happened while quiet hours held notifications back
+pub fn catch_up_summary(user_id: &str, events: &[QuietEvent]) -> SlackMessageContent {
+    let header = format!(
+        "<@{}> 🌙 {} while quiet hours were on",
+        user_id,
+        if events.len() == 1 {
+            "1 update".to_string()
+        } else {
+            format!("{} updates", events.len())
+        }
+    );
+
+    let mut lines: Vec<String> = events
+        .iter()
+        .take(MAX_CATCH_UP_EVENTS)
+        .map(|event| {
+            format!(
+                "<!date^{}^{{time}}|{}> {} *{}* · {}",
+                event.at.timestamp(),
+                event.at.format("%H:%M UTC"),
+                status_emoji(&event.status),
+                escape_mrkdwn(&event.repo),
+                event.status.short_string()
+            )
+        })
+        .collect();
+    if events.len() > MAX_CATCH_UP_EVENTS {
+        lines.push(format!("…and {} more", events.len() - MAX_CATCH_UP_EVENTS));
+    }
+
+    SlackMessageContent::new()
+        .with_text(header.clone())
+        .with_blocks(vec![
+            SlackBlock::Section(SlackSectionBlock::new().with_text(md!(header))),
+            SlackBlock::Section(SlackSectionBlock::new().with_text(md!(lines.join("\n")))),
+        ])
+}
+
+/// Permission prompt with Approve/Deny buttons
+pub fn permission_request(user_id: &str, request_id: Uuid, summary: &str) -> SlackMessageContent {
+    SlackMessageContent::new()
+        .with_text(format!("Permission needed: {}", summary))
+        .with_blocks(vec![
+            SlackBlock::Section(
+                SlackSectionBlock::new().with_text(md!(format!("<@{}> 🔐 {}", user_id, summary))),
+            ),
+            SlackBlock::Actions(SlackActionsBlock::new(vec![
+                SlackActionBlockElement::Button(
+                    SlackBlockButtonElement::new(pt!("Approve"))
+                        .with_action_id(PERMISSION_APPROVE_ACTION.into())
+                        .with_value(request_id.to_string())
+                        .with_style(SlackBlockButtonStyle::Primary),
+                ),
+                SlackActionBlockElement::Button(
+                    SlackBlockButtonElement::new(pt!("Deny"))
+                        .with_action_id(PERMISSION_DENY_ACTION.into())
+                        .with_value(request_id.to_string())
+                        .with_style(SlackBlockButtonStyle::Danger),
+                ),
+            ])),
+        ])
+}
+
+/// Permission prompt after it was answered, with the buttons replaced by the outcome
+pub fn permission_resolved(summary: &str, outcome: &str) -> SlackMessageContent {
+    SlackMessageContent::new()
+        .with_text(format!("{} ({})", summary, outcome))
+        .with_blocks(vec![
+            SlackBlock::Section(SlackSectionBlock::new().with_text(md!(format!("🔐 {}", summary)))),
+            context_block(vec![outcome.to_string()]),
+        ])
+}
+
+/// Most sessions listed by `/claude sessions`
+const MAX_LISTED_SESSIONS: usize = 20;
+
+/// Reply to `/claude sessions`, newest first
+pub fn session_list(sessions: &[Session]) -> SlackMessageContent {
+    if sessions.is_empty() {
+        return SlackMessageContent::new().with_text("No Claude Code sessions right now".into());
+    }
+
+    let mut sorted: Vec<&Session> = sessions.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+
+    let mut blocks = vec![SlackBlock::Header(SlackHeaderBlock::new(pt!(format!(
+        "Claude Code sessions ({})",
+        sessions.len()
+    ))))];
+    for session in sorted.iter().take(MAX_LISTED_SESSIONS) {
+        blocks.push(SlackBlock::Section(SlackSectionBlock::new().with_text(md!(
+            format!(
+                "{} *{}* · {} · {}\n`{}` {}",
+                status_emoji(&session.status),
+                escape_mrkdwn(&session.display_name()),
+                session.status.short_string(),
+                session.duration_string(),
+                short_id(session),
+                truncate(&escape_mrkdwn(&session.prompt), 100)
+            )
+        ))));
+    }
+    if sessions.len() > MAX_LISTED_SESSIONS {
+        blocks.push(context_block(vec![format!(
+            "…and {} older sessions",
+            sessions.len() - MAX_LISTED_SESSIONS
+        )]));
+    }
+
+    SlackMessageContent::new()
+        .with_text(format!("{} Claude Code sessions", sessions.len()))
+        .with_blocks(blocks)
+}
+
+/// Most sessions shown per status group in the App Home tab (a view holds 100 blocks)
+const MAX_HOME_SESSIONS: usize = 20;
+
+/// App Home sections, in display order
+const HOME_GROUPS: [&str; 4] = ["✋ Needs you", "⚙️ Working", "❌ Failed", "🏁 Ended"];
+
+/// Index into `HOME_GROUPS` for a status
+fn home_group(status: &SessionStatus) -> usize {
+    match status {
+        SessionStatus::WaitingForInput(_) => 0,
+        SessionStatus::Starting | SessionStatus::Running => 1,
+        SessionStatus::Failed(_) => 2,
+        SessionStatus::Completed => 3,
+    }
+}
+
+/// App Home tab: every session grouped by status, linking to threads in `permalinks`
+pub fn app_home(
+    sessions: &[Session],
+    permalinks: &HashMap<Uuid, String>,
+    updated_at: DateTime<Utc>,
+) -> SlackView {
+    let mut sorted: Vec<&Session> = sessions.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+
+    let mut blocks = vec![
+        SlackBlock::Header(SlackHeaderBlock::new(pt!("Claude Code sessions"))),
+        context_block(vec![format!(
+            "Updated <!date^{}^{{date_short_pretty}} {{time}}|{}>",
+            updated_at.timestamp(),
+            updated_at.format("%Y-%m-%d %H:%M UTC")
+        )]),
+    ];
+
+    if sessions.is_empty() {
+        blocks.push(SlackBlock::Section(
+            SlackSectionBlock::new().with_text(md!("No Claude Code sessions right now")),
+        ));
+    }
+
+    for (index, title) in HOME_GROUPS.iter().enumerate() {
+        let group: Vec<&Session> = sorted
+            .iter()
+            .copied()
+            .filter(|s| home_group(&s.status) == index)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        blocks.push(SlackBlock::Divider(SlackDividerBlock::new()));
+        blocks.push(SlackBlock::Section(SlackSectionBlock::new().with_text(md!(
+            format!("*{}* ({})", title, group.len())
+        ))));
+        for session in group.iter().take(MAX_HOME_SESSIONS) {
+            let mut line = format!(
+                "{} *{}*",
+                status_emoji(&session.status),
+                escape_mrkdwn(&session.display_name())
+            );
+            if let Some(ref branch) = session.git_branch {
+                line.push_str(&format!(" `{}`", escape_mrkdwn(branch)));
+            }
+            line.push_str(&format!(
+                " · {} · {}",
+                session.status.short_string(),
+                session.duration_string()
+            ));
+            if let Some(link) = permalinks.get(&session.id) {
+                line.push_str(&format!(" · <{}|Open thread>", link));
+            }
+            line.push_str(&format!(
+                "\n`{}` {}",
+                short_id(session),
+                truncate(&escape_mrkdwn(&session.prompt), 100)
+            ));
+            blocks.push(SlackBlock::Section(SlackSectionBlock::new().with_text(md!(line))));
+        }
+        if group.len() > MAX_HOME_SESSIONS {
+            blocks.push(context_block(vec![format!(
+                "…and {} older sessions",
+                group.len() - MAX_HOME_SESSIONS
+            )]));
+        }
+    }
+
+    SlackView::Home(SlackHomeView::new(blocks))
+}
+
+/// Reply to `/claude show`, with a link to the session thread when known
+pub fn session_details(session: &Session, permalink: Option<&str>) -> SlackMessageContent {
+    let mut fields = vec![
+        md!(format!("*Repo:*\n`{}`", escape_mrkdwn(&session.display_name()))),
+        md!(format!(
+            "*Status:*\n{} {}",
+            status_emoji(&session.status),
+            session.status.short_string()
+        )),
+        md!(format!("*Duration:*\n{}", session.duration_string())),
+        md!(format!(
+            "*Started:*\n<!date^{}^{{date_short_pretty}} {{time}}|{}>",
+            session.started_at.timestamp(),
+            session.started_at.format("%Y-%m-%d %H:%M UTC")
+        )),
+    ];
+    if let Some(ref branch) = session.git_branch {
+        fields.insert(1, md!(format!("*Branch:*\n`{}`", escape_mrkdwn(branch))));
+    }
+
+    let mut blocks = vec![
+        SlackBlock::Header(SlackHeaderBlock::new(pt!(format!(
+            "Session {}",
+            short_id(session)
+        )))),
+        SlackBlock::Section(SlackSectionBlock::new().with_text(md!(format!(
+            "*Prompt:* {}",
+            truncate(&escape_mrkdwn(&session.prompt), MAX_PROMPT_CHARS)
+        )))),
+        SlackBlock::Section(SlackSectionBlock::new().with_fields(fields)),
+    ];
+
+    let mut context = vec![format!("ID `{}`", session.id)];
+    if let Some(ref event) = session.last_event {
+        context.push(format!("Last event: {}", escape_mrkdwn(&event.description)));
+    }
+    if let Some(link) = permalink {
+        context.push(format!("<{}|Open thread>", link));
+    }
+    blocks.push(context_block(context));
+
+    SlackMessageContent::new()
+        .with_text(format!(
+            "Session {} in {}: {}",
+            short_id(session),
+            escape_mrkdwn(&session.display_name()),
+            session.status.short_string()
+        ))
+        .with_blocks(blocks)
+}
+
+/// First block of the session ID, enough to pick it with `/claude show`
+pub fn short_id(session: &Session) -> String {
+    session.id.to_string()[..8].to_string()
+}
+
+/// Emoji shown next to a status
+pub fn status_emoji(status: &SessionStatus) -> &'static str {
+    match status {
+        SessionStatus::Starting => "🚀",
+        SessionStatus::Running => "⚙️",
+        SessionStatus::WaitingForInput(WaitReason::PermissionPrompt) => "⏸️",
+        SessionStatus::WaitingForInput(WaitReason::Stopped) => "✅",
+        SessionStatus::WaitingForInput(WaitReason::PlanApproval) => "📋",
+        SessionStatus::Completed => "🏁",
+        SessionStatus::Failed(_) => "❌",
+    }
+}
+
+/// Every reaction [`status_reaction`] can return
+pub const STATUS_REACTIONS: &[&str] = &[
+    "hourglass_flowing_sand",
+    "raised_hand",
+    "white_check_mark",
+    "x",
+];
+
+/// Reaction kept on the root message for a status
+pub fn status_reaction(status: &SessionStatus) -> &'static str {
+    match status {
+        SessionStatus::Starting | SessionStatus::Running => "hourglass_flowing_sand",
+        SessionStatus::WaitingForInput(_) => "raised_hand",
+        SessionStatus::Completed => "white_check_mark",
+        SessionStatus::Failed(_) => "x",
+    }
+}
+
+/// Color of the bar beside a status section
+fn status_color(status: &SessionStatus) -> &'static str {
+    match status {
+        SessionStatus::Starting | SessionStatus::Running => "#439FE0",
+        SessionStatus::WaitingForInput(WaitReason::Stopped) => "#2EB67D",
+        SessionStatus::WaitingForInput(_) => "#ECB22E",
+        SessionStatus::Completed => "#808080",
+        SessionStatus::Failed(_) => "#E01E5A",
+    }
+}
+
+/// Wrap blocks in an attachment so Slack draws the status color beside them
+fn status_attachment(status: &SessionStatus, blocks: Vec<SlackBlock>) -> SlackMessageAttachment {
+    SlackMessageAttachment::new()
+        .with_color(status_color(status).to_string())
+        .with_blocks(blocks)
+}
+
+/// A context block of mrkdwn elements
+fn context_block(elements: Vec<String>) -> SlackBlock {
+    SlackBlock::Context(SlackContextBlock::new(
+        elements
+            .into_iter()
+            .map(|e| SlackContextBlockElement::MarkDown(SlackBlockMarkDownText::new(e)))
+            .collect(),
+    ))
+}
+
+/// Cut text to `max` characters, marking the cut with an ellipsis
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        text.chars().take(max).collect::<String>() + "…"
+    } else {
+        text.to_string()
+    }
+}
+
+/// Fill in a message template with the session's details, escaped for mrkdwn
+fn fill_template(template: &str, session: &Session, host: &str) -> String {
+    render_template(template, |name| {
+        let value = match name {
+            "repo" => session.display_name(),
+            "branch" => session.git_branch.clone().unwrap_or_default(),
+            "duration" => session.duration_string(),
+            "host" => host.to_string(),
+            "message" => match session.status {
+                SessionStatus::Failed(ref error) => error.clone(),
+                _ => session
+                    .last_event
+                    .as_ref()
+                    .map(|e| e.description.clone())
+                    .unwrap_or_default(),
+            },
+            "prompt" => truncate(&session.prompt, MAX_PROMPT_CHARS),
+            "status" => session.status.short_string().to_string(),
+            _ => String::new(),
+        };
+        escape_mrkdwn(&value)
+    })
+}
+
+/// Describe a status change for a thread reply
+fn format_status_message(session: &Session, awaiting_reply: bool) -> String {
+    match &session.status {
+        SessionStatus::Starting => "Starting Claude Code session...".to_string(),
+        SessionStatus::Running => "Claude is working on your request...".to_string(),
+        SessionStatus::WaitingForInput(reason) => match reason {
+            WaitReason::PermissionPrompt => {
+                "⏸️ Waiting for permission approval in terminal".to_string()
+            }
+            WaitReason::Stopped if awaiting_reply => {
+                "✅ Claude finished working! Reply in this thread to send the next prompt"
+                    .to_string()
+            }
+            WaitReason::Stopped => {
+                "✅ Claude finished working! Waiting for your next input in terminal".to_string()
+            }
+            WaitReason::PlanApproval => "📋 Waiting for plan approval in terminal".to_string(),
+        },
+        SessionStatus::Failed(error) => {
+            format!("❌ Session failed: {}", escape_mrkdwn(error))
+        }
+        SessionStatus::Completed => "🏁 Session ended".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use slack_code_common::session::SessionEvent;
+    use std::path::{Path, PathBuf};
+
+    fn sample_session(status: SessionStatus) -> Session {
+        let started_at = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, 0).unwrap();
+        let mut session = Session::new(
+            PathBuf::from("/home/dev/projects/webapp"),
+            Some("webapp".to_string()),
+            "Fix the login redirect <loop>".to_string(),
+        );
+        session.status = status;
+        session.started_at = started_at;
+        session.ended_at = Some(started_at + chrono::Duration::seconds(754));
+        session.git_branch = Some("fix/login".to_string());
+        session.last_event = Some(SessionEvent {
+            description: "Claude finished responding".to_string(),
+            at: started_at + chrono::Duration::seconds(754),
+        });
+        session
+    }
+
+    /// Compare content against `src/snapshots/<name>.json`.
+    /// Run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshot instead.
+    fn assert_snapshot(name: &str, content: &SlackMessageContent) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.json", name));
+        let actual = serde_json::to_value(content).unwrap();
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            let json = serde_json::to_string_pretty(&actual).unwrap();
+            std::fs::write(&path, json + "\n").unwrap();
+            return;
+        }
+
+        let expected: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            actual,
+            expected,
+            "{} does not match:\n{}",
+            path.display(),
+            serde_json::to_string_pretty(&actual).unwrap()
+        );
+    }
+
+    /// Status reply with the built-in wording, mentioning U123
+    fn default_reply(session: &Session, awaiting_reply: bool) -> SlackMessageContent {
+        let templates = TemplatesConfig::default();
+        let message = status_text(session, &templates, "devbox", awaiting_reply);
+        status_reply(session, message, Some("U123"))
+    }
+
+    #[test]
+    fn test_session_root_snapshots() {
+        let mut session = sample_session(SessionStatus::WaitingForInput(WaitReason::Stopped));
+        session.id = Uuid::parse_str("3f2a9c10-5b7e-4d21-8a6f-0c1d2e3f4a5b").unwrap();
+        let templates = TemplatesConfig::default();
+        assert_snapshot(
+            "session_root",
+            &session_root(&session, "devbox", &templates, None, false),
+        );
+        assert_snapshot(
+            "session_root_live",
+            &session_root(&session, "devbox", &templates, None, true),
+        );
+        assert_snapshot(
+            "session_root_channel",
+            &session_root(&session, "devbox", &templates, Some("U123"), false),
+        );
+    }
+
+    #[test]
+    fn test_status_reply_snapshots() {
+        let session = sample_session(SessionStatus::WaitingForInput(WaitReason::Stopped));
+        assert_snapshot("status_reply_stopped", &default_reply(&session, true));
+
+        let mut session = session;
+        session.last_message = Some(
+            "Fixed the redirect loop in `auth.rs`.\n\n**Next:** run the tests:\n```sh\ncargo test\n```"
+                .to_string(),
+        );
+        assert_snapshot(
+            "status_reply_stopped_message",
+            &default_reply(&session, false),
+        );
+
+        let session = sample_session(SessionStatus::Failed("exit code 1".to_string()));
+        assert_snapshot("status_reply_failed", &default_reply(&session, false));
+
+        let templates = TemplatesConfig(
+            [
+                ("session_start", "🚀 *{repo}* on {host}: {prompt}"),
+                (
+                    "failed",
+                    ":boom: {repo} on `{branch}` failed after {duration}: {message}",
+                ),
+            ]
+            .into_iter()
+            .map(|(name, template)| (name.to_string(), template.to_string()))
+            .collect(),
+        );
+        let message = status_text(&session, &templates, "devbox", false);
+        assert_snapshot(
+            "status_reply_template",
+            &status_reply(&session, message, Some("U123")),
+        );
+        let root = session_root(&session, "devbox", &templates, None, false);
+        assert_eq!(
+            root.text.as_deref(),
+            Some("🚀 *webapp* on devbox: Fix the login redirect &lt;loop&gt;")
+        );
+    }
+
+    #[test]
+    fn test_catch_up_summary_snapshot() {
+        let session = sample_session(SessionStatus::WaitingForInput(WaitReason::Stopped));
+        let at = Utc.with_ymd_and_hms(2025, 1, 15, 3, 12, 0).unwrap();
+        let events = vec![
+            QuietEvent {
+                at,
+                repo: "webapp".to_string(),
+                status: SessionStatus::WaitingForInput(WaitReason::PermissionPrompt),
+            },
+            QuietEvent {
+                at: at + chrono::Duration::minutes(40),
+                repo: "webapp".to_string(),
+                status: session.status.clone(),
+            },
+        ];
+        assert_snapshot("catch_up_summary", &catch_up_summary("U123", &events));
+    }
+
+    #[test]
+    fn test_session_command_snapshots() {
+        let mut session = sample_session(SessionStatus::Running);
+        session.id = Uuid::parse_str("3f2a9c10-5b7e-4d21-8a6f-0c1d2e3f4a5b").unwrap();
+        assert_snapshot("session_list", &session_list(std::slice::from_ref(&session)));
+        assert_snapshot(
+            "session_details",
+            &session_details(
+                &session,
+                Some("https://example.slack.com/archives/D123/p1736933400000100"),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_app_home_snapshot() {
+        let mut waiting = sample_session(SessionStatus::WaitingForInput(WaitReason::Stopped));
+        waiting.id = Uuid::parse_str("3f2a9c10-5b7e-4d21-8a6f-0c1d2e3f4a5b").unwrap();
+        let mut running = sample_session(SessionStatus::Running);
+        running.id = Uuid::parse_str("7c4d2e1f-0a9b-4c8d-9e7f-6a5b4c3d2e1f").unwrap();
+        running.repo_alias = Some("api".to_string());
+        running.git_branch = None;
+
+        let permalinks = HashMap::from([(
+            waiting.id,
+            "https://example.slack.com/archives/D123/p1736933400000100".to_string(),
+        )]);
+        let updated_at = Utc.with_ymd_and_hms(2025, 1, 15, 9, 45, 0).unwrap();
+        let view = app_home(&[running, waiting], &permalinks, updated_at);
+        let SlackView::Home(home) = view else {
+            panic!("not a home view");
+        };
+        assert_snapshot(
+            "app_home",
+            &SlackMessageContent::new().with_blocks(home.blocks),
+        );
+    }
+
+    #[test]
+    fn test_permission_snapshots() {
+        let request_id = Uuid::parse_str("6f1c1f8e-3a55-4bb1-9a0c-2f4e1d2c3b4a").unwrap();
+        let summary = "Claude wants to use *Bash*\n```cargo test```";
+        assert_snapshot(
+            "permission_request",
+            &permission_request("U123", request_id, summary),
+        );
+        assert_snapshot(
+            "permission_resolved",
+            &permission_resolved(summary, "✅ Approved by <@U123>"),
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Checks that the configured Slack tokens and user actually work.
+//!
+//! Shared by the TUI's "Test tokens" action and `slack-code slack test`.
+
+use crate::slack::new_client;
+use anyhow::{bail, Context, Result};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use slack_code_common::config::SlackConfig;
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::prelude::*;
+use std::fmt;
+
+/// Bot scopes slack-code can't work without
+pub const REQUIRED_SCOPES: &[&str] = &["chat:write", "im:write", "users:read", "reactions:write"];
+
+const DEFAULT_API_URL: &str = "https://slack.com/api";
+
+/// Who the bot token belongs to, from `auth.test`
+#[derive(Debug, Clone)]
+pub struct BotIdentity {
+    pub team: String,
+    pub user: String,
+    /// Granted scopes, when Slack reports them
+    pub scopes: Option<Vec<String>>,
+}
+
+impl BotIdentity {
+    /// Required scopes the token lacks
+    pub fn missing_scopes(&self) -> Vec<&'static str> {
+        match self.scopes {
+            Some(ref scopes) => REQUIRED_SCOPES
+                .iter()
+                .copied()
+                .filter(|required| !scopes.iter().any(|s| s == required))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// How one check went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed(String),
+    Failed(String),
+    /// Not run because an earlier check failed or nothing is configured
+    Skipped(String),
+}
+
+/// One named check and its outcome
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+}
+
+impl Check {
+    fn new(name: &'static str, outcome: Outcome) -> Self {
+        Self { name, outcome }
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Failed(_))
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.outcome {
+            Outcome::Passed(ref detail) => write!(f, "✓ {}: {}", self.name, detail),
+            Outcome::Failed(ref detail) => write!(f, "✗ {}: {}", self.name, detail),
+            Outcome::Skipped(ref detail) => write!(f, "- {}: {}", self.name, detail),
+        }
+    }
+}
+
+/// Call `auth.test` with a bot token, reading the granted scopes from the
+/// `x-oauth-scopes` header (which the Slack client doesn't expose)
+pub async fn auth_test(api_url: Option<&str>, bot_token: &str) -> Result<BotIdentity> {
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()?
+        .https_or_http()
+        .enable_http1()
+        .enable_http2()
+        .build();
+    let client: Client<_, Full<Bytes>> = Client::builder(TokioExecutor::new()).build(https);
+
+    let url = format!(
+        "{}/auth.test",
+        api_url.unwrap_or(DEFAULT_API_URL).trim_end_matches('/')
+    );
+    let request = http::Request::post(url)
+        .header(http::header::AUTHORIZATION, format!("Bearer {}", bot_token))
+        .body(Full::new(Bytes::new()))?;
+    let response = client
+        .request(request)
+        .await
+        .context("Could not reach the Slack API")?;
+
+    let scopes = response
+        .headers()
+        .get("x-oauth-scopes")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        });
+    let status = response.status();
+    let body = response.into_body().collect().await?.to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body)
+        .with_context(|| format!("Unexpected auth.test response (HTTP {})", status))?;
+
+    if body["ok"] != true {
+        bail!(
+            "Slack rejected the token: {}",
+            body["error"].as_str().unwrap_or("unknown error")
+        );
+    }
+
+    Ok(BotIdentity {
+        team: body["team"].as_str().unwrap_or_default().to_string(),
+        user: body["user"].as_str().unwrap_or_default().to_string(),
+        scopes,
+    })
+}
+
+/// Look up a member by ID, rejecting bots and deactivated accounts
+pub async fn user_info(api_url: Option<&str>, bot_token: &str, user_id: &str) -> Result<SlackUser> {
+    let client = new_client(api_url)?;
+    let token = SlackApiToken::new(bot_token.into());
+    let response = client
+        .open_session(&token)
+        .users_info(&SlackApiUsersInfoRequest::new(user_id.into()))
+        .await
+        .with_context(|| format!("Could not look up {}", user_id))?;
+
+    usable_user(response.user)
+}
+
+/// Ask for a Socket Mode connection with an app token
+pub async fn socket_mode_check(api_url: Option<&str>, app_token: &str) -> Result<()> {
+    let client = new_client(api_url)?;
+    let token = SlackApiToken::new(app_token.into());
+    client
+        .open_session(&token)
+        .apps_connections_open(&SlackApiAppsConnectionOpenRequest::new())
+        .await?;
+    Ok(())
+}
+
+/// Find a member by the email address of their Slack account
+/// (needs the `users:read.email` scope)
+pub async fn lookup_user_by_email(
+    api_url: Option<&str>,
+    bot_token: &str,
+    email: &str,
+) -> Result<SlackUser> {
+    let client = new_client(api_url)?;
+    let token = SlackApiToken::new(bot_token.into());
+    let request = SlackApiUsersLookupByEmailRequest::new(EmailAddress(email.to_string()));
+    let response = client
+        .open_session(&token)
+        .users_lookup_by_email(&request)
+        .await
+        .with_context(|| format!("Could not look up {}", email))?;
+    usable_user(response.user)
+}
+
+/// Reject bots and deactivated accounts, which can't receive notifications
+fn usable_user(user: SlackUser) -> Result<SlackUser> {
+    if user.deleted == Some(true) {
+        bail!("{} is deactivated", user.id);
+    }
+    if user.flags.is_bot == Some(true) {
+        bail!("{} is a bot, not a person", user.id);
+    }
+    Ok(user)
+}
+
+/// Slack's error code, when a call failed with one (e.g. `users_not_found`)
+pub fn slack_error_code(error: &anyhow::Error) -> Option<&str> {
+    match error.downcast_ref::<SlackClientError>() {
+        Some(SlackClientError::ApiError(e)) => Some(&e.code),
+        _ => None,
+    }
+}
+
+/// Name to show for a member: display name, real name or handle
+pub fn user_display_name(user: &SlackUser) -> String {
+    let profile = user.profile.as_ref();
+    profile
+        .and_then(|p| p.display_name.clone())
+        .filter(|n| !n.is_empty())
+        .or_else(|| profile.and_then(|p| p.real_name.clone()))
+        .or_else(|| user.real_name.clone())
+        .or_else(|| user.name.clone())
+        .unwrap_or_else(|| user.id.to_string())
+}
+
+/// Run every check in order; later checks are skipped once one they depend on fails
+pub async fn check_slack(slack: &SlackConfig) -> Vec<Check> {
+    let api_url = slack.api_url.as_deref();
+    let bot_token = slack.get_bot_token();
+    let mut checks = Vec::new();
+
+    // Bot token and its scopes
+    let identity = if bot_token.is_empty() {
+        checks.push(Check::new(
+            "Bot token",
+            Outcome::Failed("not set (bot_token or SLACK_CODE_BOT_TOKEN)".to_string()),
+        ));
+        None
+    } else {
+        match auth_test(api_url, &bot_token).await {
+            Ok(identity) => {
+                checks.push(Check::new(
+                    "Bot token",
+                    Outcome::Passed(format!("@{} in {}", identity.user, identity.team)),
+                ));
+                Some(identity)
+            }
+            Err(e) => {
+                checks.push(Check::new("Bot token", Outcome::Failed(format!("{:#}", e))));
+                None
+            }
+        }
+    };
+
+    let scopes = match identity {
+        None => Outcome::Skipped("needs a working bot token".to_string()),
+        Some(ref identity) if identity.scopes.is_none() => {
+            Outcome::Skipped("Slack did not report the token's scopes".to_string())
+        }
+        Some(ref identity) => match identity.missing_scopes() {
+            missing if missing.is_empty() => Outcome::Passed(REQUIRED_SCOPES.join(", ")),
+            missing => Outcome::Failed(format!(
+                "missing {}; add under OAuth & Permissions and reinstall the app",
+                missing.join(", ")
+            )),
+        },
+    };
+    checks.push(Check::new("Bot scopes", scopes));
+
+    // The member notifications go to
+    let user_ok = match identity {
+        None => {
+            checks.push(Check::new(
+                "User ID",
+                Outcome::Skipped("needs a working bot token".to_string()),
+            ));
+            false
+        }
+        Some(_) if slack.user_id.is_empty() => {
+            checks.push(Check::new(
+                "User ID",
+                Outcome::Failed("not set, run 'slack-code setup'".to_string()),
+            ));
+            false
+        }
+        Some(_) => match user_info(api_url, &bot_token, &slack.user_id).await {
+            Ok(user) => {
+                checks.push(Check::new(
+                    "User ID",
+                    Outcome::Passed(format!("{} is {}", slack.user_id, user_display_name(&user))),
+                ));
+                true
+            }
+            Err(e) => {
+                checks.push(Check::new("User ID", Outcome::Failed(format!("{:#}", e))));
+                false
+            }
+        },
+    };
+
+    let dm = if user_ok {
+        let open = async {
+            let client = new_client(api_url)?;
+            let token = SlackApiToken::new(bot_token.clone().into());
+            let request = SlackApiConversationsOpenRequest::new()
+                .with_users(vec![SlackUserId::new(slack.user_id.clone())]);
+            anyhow::Ok(
+                client
+                    .open_session(&token)
+                    .conversations_open(&request)
+                    .await?,
+            )
+        };
+        match open.await {
+            Ok(response) => Outcome::Passed(format!("opened {}", response.channel.id)),
+            Err(e) => Outcome::Failed(format!("Could not open a DM: {:#}", e)),
+        }
+    } else {
+        Outcome::Skipped("needs a valid user ID".to_string())
+    };
+    checks.push(Check::new("Direct message", dm));
+
+    // Socket Mode is optional
+    let app_token = slack.get_app_token();
+    let socket_mode = if app_token.is_empty() {
+        Outcome::Skipped("not set, so nothing can be received from Slack".to_string())
+    } else {
+        match socket_mode_check(api_url, &app_token).await {
+            Ok(()) => Outcome::Passed("Socket Mode connection allowed".to_string()),
+            Err(e) => Outcome::Failed(format!("{:#}", e)),
+        }
+    };
+    checks.push(Check::new("App token", socket_mode));
+
+    checks
+}
@@ -0,0 +1,70 @@
+/// A `/claude` slash command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// List all sessions with status and duration
+    Sessions,
+
+    /// Show one session, picked by a prefix of its ID
+    Show(String),
+
+    /// Start Claude Code in the repository with this alias
+    Run { alias: String, prompt: String },
+
+    /// Explain the available commands
+    Help,
+}
+
+impl SlashCommand {
+    /// Parse the text typed after the command name
+    pub fn parse(text: &str) -> Self {
+        if let Some(rest) = text.trim_start().strip_prefix("run ") {
+            let rest = rest.trim();
+            return match rest.split_once(char::is_whitespace) {
+                Some((alias, prompt)) => SlashCommand::Run {
+                    alias: alias.to_string(),
+                    prompt: prompt.trim().to_string(),
+                },
+                None => SlashCommand::Help,
+            };
+        }
+
+        let mut words = text.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("sessions" | "list" | "ls"), None) => SlashCommand::Sessions,
+            (Some("show"), Some(prefix)) => SlashCommand::Show(prefix.to_lowercase()),
+            _ => SlashCommand::Help,
+        }
+    }
+}
+
+/// Usage shown for `/claude help` and unrecognized input
+pub const USAGE: &str = "*Usage:*\n\
+    `/claude sessions` list your Claude Code sessions\n\
+    `/claude show <id-prefix>` show a session and link to its thread\n\
+    `/claude run <repo> <prompt>` start Claude Code in a repo from your config";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(SlashCommand::parse("sessions"), SlashCommand::Sessions);
+        assert_eq!(SlashCommand::parse("  sessions "), SlashCommand::Sessions);
+        assert_eq!(
+            SlashCommand::parse("show 3F2A"),
+            SlashCommand::Show("3f2a".to_string())
+        );
+        assert_eq!(SlashCommand::parse("show"), SlashCommand::Help);
+        assert_eq!(SlashCommand::parse(""), SlashCommand::Help);
+        assert_eq!(SlashCommand::parse("sessions extra"), SlashCommand::Help);
+        assert_eq!(
+            SlashCommand::parse("run api  Fix the flaky test\nthen push"),
+            SlashCommand::Run {
+                alias: "api".to_string(),
+                prompt: "Fix the flaky test\nthen push".to_string(),
+            }
+        );
+        assert_eq!(SlashCommand::parse("run api"), SlashCommand::Help);
+    }
+}
@@ -2,17 +2,48 @@ use anyhow::Result;
 use daemonize::Daemonize;
 use nix::sys::signal::{kill, Signal};
 use nix::unistd::Pid;
-use slack_code_common::ipc::{DaemonCommand, DaemonEvent, DaemonStatus, HookEvent};
-use slack_code_common::session::SessionStatus;
+use slack_code_common::ipc::{
+    DaemonCommand, DaemonEvent, DaemonStatus, HookEvent, HookResponse, PermissionDecision,
+};
+use slack_code_common::session::{Session, SessionStatus, SlackThread};
 use slack_code_common::Config;
+use slack_morphism::prelude::*;
 use std::fs::File;
 use std::io::Read as _;
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc, RwLock};
-
-use crate::ipc::{IpcClient, IpcServer};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, watch, RwLock};
+use uuid::Uuid;
+
+use crate::blocks::{
+    self, PERMISSION_APPROVE_ACTION, PERMISSION_DENY_ACTION, STOP_SESSION_ACTION,
+};
+use crate::commands::{SlashCommand, USAGE};
+use crate::exec::ExecNotifier;
+use crate::home::HomePublisher;
+use crate::ipc::{HookMessage, IpcClient, IpcServer};
+use crate::launch::{LaunchExit, Launcher};
+use crate::notify::{Notifiers, SessionUpdate, SlackNotifier};
+use crate::outbox::{OutboxMessage, OutboxSender, OutboxWorker};
+use crate::pending::{PendingRequests, ResolvedPermission};
 use crate::session::SessionManager;
-use crate::slack::SlackService;
+use crate::slack::{describe_tool_call, SlackMessageRef, SlackService};
+use crate::socket_mode::{SocketModeClient, SocketModeEvent};
+use crate::stop::{stop_process, StopOutcome};
+use crate::transcript;
+use crate::webhook::WebhookNotifier;
+
+/// How stopping a session's Claude Code went: `Err` if it couldn't be signalled
+type StopResult = (Uuid, Result<StopOutcome, String>);
+
+/// A permission prompt posted to Slack, whose hook now waits for a button press
+struct PostedPermission {
+    request_id: Uuid,
+    session_id: Uuid,
+    message: SlackMessageRef,
+    summary: String,
+    reply_tx: oneshot::Sender<HookResponse>,
+}
 
 /// Main daemon process
 pub struct Daemon {
@@ -23,7 +54,8 @@ pub struct Daemon {
 impl Daemon {
     /// Create a new daemon instance
     pub fn new(config: Config) -> Result<Self> {
-        let session_manager = Arc::new(RwLock::new(SessionManager::new()));
+        let session_manager =
+            Arc::new(RwLock::new(SessionManager::with_repos(config.repo_paths())));
 
         Ok(Self {
             config,
@@ -197,24 +229,21 @@ impl Daemon {
         kill(Pid::from_raw(pid), None).is_ok()
     }
 
-    /// Main daemon event loop
-    async fn run(self) -> Result<()> {
+    /// Main daemon event loop; runs until SIGTERM or SIGINT
+    pub async fn run(self) -> Result<()> {
         tracing::info!("Starting slack-code daemon");
 
         // Create channels
-        let (hook_tx, mut hook_rx) = mpsc::channel::<HookEvent>(100);
+        let (hook_tx, mut hook_rx) = mpsc::channel::<HookMessage>(100);
         let (command_tx, mut command_rx) = mpsc::channel::<DaemonCommand>(100);
         let (event_tx, _) = broadcast::channel::<DaemonEvent>(100);
 
         // Initialize Slack service
-        let bot_token = self.config.slack.get_bot_token();
-        let user_id = self.config.slack.user_id.clone();
-
-        let slack_service = if !bot_token.is_empty() {
-            match SlackService::new(&bot_token, user_id) {
+        let slack_service = if !self.config.slack.get_bot_token().is_empty() {
+            match SlackService::new(&self.config) {
                 Ok(service) => {
                     tracing::info!("Slack service initialized");
-                    Some(Arc::new(RwLock::new(service)))
+                    Some(Arc::new(service))
                 }
                 Err(e) => {
                     tracing::warn!("Failed to initialize Slack service: {}", e);
@@ -226,6 +255,88 @@ impl Daemon {
             None
         };
 
+        // Deliver notifications through the outbox, which retries and survives restarts
+        let (thread_tx, mut thread_rx) = mpsc::unbounded_channel::<(Uuid, SlackThread)>();
+        let outbox = slack_service.as_ref().map(|slack| {
+            let (worker, sender) =
+                OutboxWorker::new(self.config.daemon.outbox_file.clone(), slack.clone(), thread_tx);
+            tokio::spawn(worker.run());
+            sender
+        });
+
+        // Report sessions to every enabled notification backend
+        let mut notifiers = Notifiers::new();
+        let notifiers_config = &self.config.notifiers;
+        if let (Some(ref slack), Some(ref outbox)) = (&slack_service, &outbox) {
+            if notifiers_config.slack.enabled {
+                let notifier = Arc::new(SlackNotifier::new(
+                    slack.clone(),
+                    outbox.clone(),
+                    self.session_manager.clone(),
+                    Duration::from_secs(self.config.notifications.settle_seconds),
+                ));
+                tokio::spawn(notifier.clone().run());
+                notifiers.add(notifier, notifiers_config.slack.events.clone());
+            }
+        }
+        for webhook in &notifiers_config.webhook {
+            match WebhookNotifier::new(webhook) {
+                Ok(notifier) => notifiers.add(Arc::new(notifier), webhook.events.clone()),
+                Err(e) => tracing::error!("Webhook notifier disabled: {:#}", e),
+            }
+        }
+        for exec in &notifiers_config.exec {
+            notifiers.add(Arc::new(ExecNotifier::new(exec)), exec.events.clone());
+        }
+
+        // Keep the App Home tab in sync with the sessions
+        let home_refresh = slack_service.as_ref().map(|slack| {
+            let (publisher, refresh) = HomePublisher::new(
+                slack.clone(),
+                self.session_manager.clone(),
+                event_tx.subscribe(),
+            );
+            tokio::spawn(publisher.run());
+            refresh
+        });
+
+        // Sessions started with `/claude run` report back when Claude Code exits
+        let (launch_tx, mut launch_rx) = mpsc::unbounded_channel::<LaunchExit>();
+        let launcher = Launcher::new(self.config.launch.command.clone(), launch_tx);
+
+        // Stopping a session's Claude Code takes up to two grace periods, so it runs on the side
+        let (stop_tx, mut stop_rx) = mpsc::unbounded_channel::<StopResult>();
+        let stop_grace = Duration::from_secs(self.config.interaction.stop_grace);
+
+        // Permission prompts are posted on the side and waited on once they are up
+        let (posted_tx, mut posted_rx) = mpsc::unbounded_channel::<PostedPermission>();
+
+        // Start Socket Mode connection for inbound Slack events
+        let (socket_tx, mut socket_rx) = mpsc::channel::<SocketModeEvent>(100);
+        let (status_tx, mut status_rx) = watch::channel(DaemonStatus::Disconnected(
+            "Socket Mode not started".to_string(),
+        ));
+
+        let app_token = self.config.slack.get_app_token();
+        if app_token.is_empty() {
+            tracing::warn!("Slack app token not configured, Socket Mode disabled");
+            status_tx.send_replace(DaemonStatus::Disconnected(
+                "App token not configured".to_string(),
+            ));
+        } else {
+            match crate::slack::new_client(self.config.slack.api_url.as_deref()) {
+                Ok(client) => {
+                    let socket_client =
+                        SocketModeClient::new(client, &app_token, socket_tx, status_tx.clone());
+                    tokio::spawn(socket_client.run());
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to initialize Socket Mode client: {}", e);
+                    status_tx.send_replace(DaemonStatus::Disconnected(e.to_string()));
+                }
+            }
+        }
+
         // Start IPC server
         let ipc_server = IpcServer::new(
             self.config.daemon.socket_path.clone(),
@@ -249,47 +360,259 @@ impl Daemon {
         // Main event loop
         let session_manager = self.session_manager.clone();
         let event_tx_clone = event_tx.clone();
+        let mut pending = PendingRequests::new();
+        let mut expiry_tick = tokio::time::interval(Duration::from_secs(1));
 
         loop {
             tokio::select! {
                 // Handle hook events from Claude Code
-                Some(hook_event) = hook_rx.recv() => {
+                Some(HookMessage { event: hook_event, reply_tx }) = hook_rx.recv() => {
                     tracing::debug!("Received hook event: {:?}", hook_event);
 
-                    let mut manager = session_manager.write().await;
-                    if let Some((mut session, status_changed)) = manager.handle_hook_event(hook_event) {
-                        // If session has no Slack thread, create one (for external sessions)
-                        if session.slack_thread.is_none() {
-                            if let Some(ref slack) = slack_service {
-                                let mut slack = slack.write().await;
-                                match slack.post_session_start(&session).await {
-                                    Ok(thread) => {
-                                        manager.set_slack_thread(session.id, thread.clone());
-                                        session.slack_thread = Some(thread);
-                                        tracing::info!("Created Slack thread for session: {}", session.id);
+                    // Keep the tool call around for the permission prompt
+                    let permission_summary = match &hook_event {
+                        HookEvent::PermissionRequest { tool_name, tool_input, .. } => {
+                            Some(describe_tool_call(tool_name, tool_input))
+                        }
+                        _ => None,
+                    };
+                    // The user just sent the prompt, so Slack has nothing to tell them
+                    let quiet = matches!(hook_event, HookEvent::UserPromptSubmit { .. });
+
+                    let slack_connected = *status_rx.borrow() == DaemonStatus::Connected;
+                    // Transcripts can be large, so Claude's reply is read before taking the lock
+                    let last_message = match &hook_event {
+                        HookEvent::Stop { session_id, .. } => {
+                            read_last_message(&session_manager, session_id).await
+                        }
+                        _ => None,
+                    };
+
+                    let handled = {
+                        let mut manager = session_manager.write().await;
+                        if let HookEvent::Stop { session_id, .. } = &hook_event {
+                            manager.set_last_message(session_id, last_message);
+                        }
+                        manager.handle_hook_event(hook_event)
+                    };
+                    if let Some((session, status_changed)) = handled {
+                        // A waiting hook can only be answered through the session thread.
+                        // Dropping reply_tx answers it with NoReply.
+                        let reply_tx = reply_tx
+                            .filter(|_| slack_connected && session.slack_thread.is_some());
+                        let (permission_tx, reply_tx) = if permission_summary.is_some() {
+                            (reply_tx, None)
+                        } else {
+                            (None, reply_tx)
+                        };
+
+                        // Ask with Approve/Deny buttons instead of the plain status reply.
+                        // The prompt is only useful while the hook waits, so it skips the outbox.
+                        let mut permission_prompt = false;
+                        if let (Some(ref slack), Some(summary), Some(reply_tx), Some(thread)) =
+                            (&slack_service, permission_summary, permission_tx, session.slack_thread.clone())
+                        {
+                            permission_prompt = true;
+                            let (slack, posted_tx, session_id) = (slack.clone(), posted_tx.clone(), session.id);
+                            tokio::spawn(async move {
+                                let request_id = Uuid::new_v4();
+                                match slack.post_permission_request(&thread, request_id, &summary).await {
+                                    Ok(message) => {
+                                        let _ = posted_tx.send(PostedPermission {
+                                            request_id,
+                                            session_id,
+                                            message,
+                                            summary,
+                                            reply_tx,
+                                        });
                                     }
+                                    // Dropping reply_tx leaves the decision to the terminal
                                     Err(e) => {
-                                        tracing::warn!("Failed to create Slack thread: {}", e);
+                                        tracing::warn!("Failed to post permission request: {}", e);
                                     }
                                 }
+                            });
+                        }
+
+                        notifiers.session_updated(SessionUpdate {
+                            session: session.clone(),
+                            status_changed,
+                            quiet,
+                            awaiting_reply: reply_tx.is_some(),
+                            permission_prompt,
+                        });
+
+                        if let (Some(reply_tx), Some(thread)) = (reply_tx, &session.slack_thread) {
+                            tracing::info!("Waiting for a thread reply for session: {}", session.id);
+                            pending.wait_for_reply(thread, session.id, reply_tx);
+                        }
+
+                        // Broadcast session update
+                        let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(Box::new(session.clone())));
+                    }
+                }
+
+                // Wait for a button press on permission prompts that made it to Slack
+                Some(posted) = posted_rx.recv() => {
+                    pending.wait_for_permission(
+                        posted.request_id,
+                        posted.session_id,
+                        posted.message,
+                        posted.summary,
+                        Duration::from_secs(self.config.interaction.permission_timeout),
+                        posted.reply_tx,
+                    );
+                }
+
+                // Apply the fallback to permission requests nobody answered
+                _ = expiry_tick.tick() => {
+                    let fallback = self.config.interaction.permission_fallback;
+                    for resolved in pending.expire_permissions(Instant::now(), fallback) {
+                        let outcome = match fallback {
+                            PermissionDecision::Allow => "⌛ No answer in time, allowed automatically",
+                            PermissionDecision::Deny => "⌛ No answer in time, denied automatically",
+                            PermissionDecision::Ask => "⌛ No answer in time, asking in the terminal",
+                        };
+                        finish_permission_request(
+                            resolved,
+                            fallback,
+                            outcome,
+                            &session_manager,
+                            slack_service.as_ref(),
+                            &mut notifiers,
+                            &event_tx_clone,
+                        )
+                        .await;
+                    }
+                }
+
+                // Handle inbound Slack events
+                Some(socket_event) = socket_rx.recv() => {
+                    tracing::debug!("Received Socket Mode event: {:?}", socket_event);
+
+                    match socket_event {
+                        SocketModeEvent::EventsApi(callback) => match callback.event {
+                            SlackEventCallbackBody::Message(message) => {
+                                handle_thread_message(
+                                    message,
+                                    &self.config.slack.user_id,
+                                    &session_manager,
+                                    &mut pending,
+                                    outbox.as_ref(),
+                                    &mut notifiers,
+                                    &event_tx_clone,
+                                )
+                                .await;
+                            }
+                            SlackEventCallbackBody::AppHomeOpened(opened)
+                                if opened.user.to_string() == self.config.slack.user_id =>
+                            {
+                                if let Some(ref refresh) = home_refresh {
+                                    refresh.notify_one();
+                                }
                             }
-                        } else if status_changed && !matches!(session.status, SessionStatus::Completed) {
-                            // Only post status update if status changed (skip Completed status)
+                            _ => {}
+                        },
+                        SocketModeEvent::Interactive(interaction) => {
+                            if let SlackInteractionEvent::BlockActions(actions) = *interaction {
+                                let stop_requests = handle_block_actions(
+                                    actions,
+                                    &self.config.slack.user_id,
+                                    &session_manager,
+                                    &mut pending,
+                                    slack_service.as_ref(),
+                                    &mut notifiers,
+                                    &event_tx_clone,
+                                )
+                                .await;
+                                for session_id in stop_requests {
+                                    stop_session(
+                                        session_id,
+                                        &format!("by <@{}>", self.config.slack.user_id),
+                                        &session_manager,
+                                        outbox.as_ref(),
+                                        stop_grace,
+                                        &stop_tx,
+                                    )
+                                    .await;
+                                }
+                            }
+                        }
+                        SocketModeEvent::SlashCommand(command) => {
                             if let Some(ref slack) = slack_service {
-                                if let Some(ref thread) = session.slack_thread {
-                                    let slack = slack.read().await;
-                                    if let Err(e) = slack.post_thread_reply(thread, &session).await {
-                                        tracing::warn!("Failed to post to Slack: {}", e);
-                                    }
+                                let launched = handle_slash_command(
+                                    *command,
+                                    &self.config,
+                                    &session_manager,
+                                    slack,
+                                    &launcher,
+                                )
+                                .await;
+
+                                // Post the thread for a session started from Slack right away
+                                if let Some(session) = launched {
+                                    notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+                                    let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(Box::new(session)));
                                 }
                             }
                         }
+                    }
+                }
 
-                        // Broadcast session update
-                        let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(session.clone()));
+                // Record threads once the outbox has posted their root message
+                Some((session_id, thread)) = thread_rx.recv() => {
+                    tracing::info!("Created Slack thread for session: {}", session_id);
+                    let mut manager = session_manager.write().await;
+                    manager.set_slack_thread(session_id, thread);
+                    if let Some(session) = manager.get_session(&session_id) {
+                        let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(Box::new(session.clone())));
+                    }
+                }
+
+                // Settle sessions whose Claude Code exited without ending them
+                Some((session_id, result)) = launch_rx.recv() => {
+                    if let Err(ref reason) = result {
+                        tracing::warn!("Claude Code for session {} failed: {}", session_id, reason);
+                    }
+                    let finished = session_manager.write().await.process_exited(&session_id, result);
+                    if let Some(session) = finished {
+                        notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+                        let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(Box::new(session)));
                     }
                 }
 
+                // Log how stopping a session went, and end it once Claude Code is gone
+                Some((session_id, result)) = stop_rx.recv() => {
+                    let (notice, exited) = match result {
+                        Ok(outcome) => (outcome.describe().to_string(), outcome.exited()),
+                        Err(e) => (format!("⚠️ Could not stop Claude Code: {}", e), false),
+                    };
+                    tracing::info!("Stopping session {}: {}", session_id, notice);
+
+                    let mut manager = session_manager.write().await;
+                    let thread = manager.get_session(&session_id).and_then(|s| s.slack_thread.clone());
+                    let finished = if exited {
+                        manager.process_exited(&session_id, Ok(()))
+                    } else {
+                        None
+                    };
+                    drop(manager);
+
+                    if let Some(ref outbox) = outbox {
+                        outbox.send(session_id, thread.as_ref(), [OutboxMessage::reply_text(notice)]);
+                    }
+                    if let Some(session) = finished {
+                        notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+                        let _ = event_tx_clone.send(DaemonEvent::SessionUpdated(Box::new(session)));
+                    }
+                }
+
+                // Report Slack connection changes to subscribers
+                Ok(()) = status_rx.changed() => {
+                    let status = status_rx.borrow_and_update().clone();
+                    let _ = event_tx_clone.send(DaemonEvent::Status(status));
+                }
+
                 // Handle TUI commands
                 Some(cmd) = command_rx.recv() => {
                     tracing::debug!("Received command: {:?}", cmd);
@@ -301,10 +624,22 @@ impl Daemon {
                             let _ = event_tx_clone.send(DaemonEvent::SessionList(sessions));
                         }
                         DaemonCommand::GetConfig => {
-                            let _ = event_tx_clone.send(DaemonEvent::ConfigResponse(self.config.clone()));
+                            let _ = event_tx_clone.send(DaemonEvent::ConfigResponse(Box::new(self.config.clone())));
                         }
                         DaemonCommand::Ping => {
-                            let _ = event_tx_clone.send(DaemonEvent::Status(DaemonStatus::Connected));
+                            let status = status_rx.borrow().clone();
+                            let _ = event_tx_clone.send(DaemonEvent::Status(status));
+                        }
+                        DaemonCommand::StopSession(session_id) => {
+                            stop_session(
+                                session_id,
+                                "from the TUI",
+                                &session_manager,
+                                outbox.as_ref(),
+                                stop_grace,
+                                &stop_tx,
+                            )
+                            .await;
                         }
                         _ => {}
                     }
@@ -333,3 +668,333 @@ impl Daemon {
         Ok(())
     }
 }
+
+/// Claude's latest reply, read off the async threads
+async fn read_last_message(
+    session_manager: &RwLock<SessionManager>,
+    claude_session_id: &str,
+) -> Option<String> {
+    let path = session_manager.read().await.transcript_path(claude_session_id)?;
+    tokio::task::spawn_blocking(move || transcript::last_assistant_message(&path))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Relay the user's reply in a session thread to the Stop hook waiting on it
+async fn handle_thread_message(
+    message: SlackMessageEvent,
+    user_id: &str,
+    session_manager: &RwLock<SessionManager>,
+    pending: &mut PendingRequests,
+    outbox: Option<&OutboxSender>,
+    notifiers: &mut Notifiers,
+    event_tx: &broadcast::Sender<DaemonEvent>,
+) {
+    // Only plain messages from the configured user, inside a thread
+    let from_user = message.sender.user.as_ref().is_some_and(|u| u.0 == user_id);
+    if message.subtype.is_some() || message.sender.bot_id.is_some() || !from_user {
+        return;
+    }
+    let (Some(channel), Some(thread_ts)) = (&message.origin.channel, &message.origin.thread_ts)
+    else {
+        return;
+    };
+    let text = message
+        .content
+        .as_ref()
+        .and_then(|c| c.text.clone())
+        .unwrap_or_default();
+    if text.trim().is_empty() {
+        return;
+    }
+
+    let mut manager = session_manager.write().await;
+    let Some(session) = manager.find_by_thread(&channel.0, &thread_ts.0).cloned() else {
+        return;
+    };
+    let Some(thread) = session.slack_thread.clone() else {
+        return;
+    };
+
+    let delivered = pending.deliver_reply(&channel.0, &thread_ts.0, text);
+    let (notice, updated) = if delivered.is_some() {
+        tracing::info!("Sent thread reply to session: {}", session.id);
+        let updated =
+            manager.set_status(&session.id, SessionStatus::Running, "Prompt sent from Slack");
+        if let Some((ref session, _)) = updated {
+            let _ = event_tx.send(DaemonEvent::SessionUpdated(Box::new(session.clone())));
+        }
+        ("▶️ Sent to Claude", updated)
+    } else {
+        (
+            "⚠️ Claude isn't waiting for a reply in this session right now",
+            None,
+        )
+    };
+
+    if let Some(outbox) = outbox {
+        outbox.send(session.id, Some(&thread), [OutboxMessage::reply_text(notice)]);
+    }
+    if let Some((session, changed)) = updated {
+        notifiers.session_updated(SessionUpdate::quiet(session, changed));
+    }
+}
+
+/// Answer `/claude` with an ephemeral reply, returning the session
+/// `/claude run` started
+async fn handle_slash_command(
+    command: SlackCommandEvent,
+    config: &Config,
+    session_manager: &RwLock<SessionManager>,
+    slack: &Arc<SlackService>,
+    launcher: &Launcher,
+) -> Option<Session> {
+    let user_id = &config.slack.user_id;
+    let response_url = command.response_url.clone();
+    let mut launched = None;
+
+    let content = if command.user_id.0 != *user_id {
+        SlackMessageContent::new().with_text(format!(
+            "These Claude Code sessions belong to <@{}>",
+            user_id
+        ))
+    } else {
+        match SlashCommand::parse(command.text.as_deref().unwrap_or_default()) {
+            SlashCommand::Sessions => {
+                let manager = session_manager.read().await;
+                blocks::session_list(&manager.get_sessions())
+            }
+            SlashCommand::Show(prefix) => {
+                let session = {
+                    let manager = session_manager.read().await;
+                    match manager.find_by_prefix(&prefix).as_slice() {
+                        [session] => Ok((*session).clone()),
+                        [] => Err(format!("No session matches `{}`", prefix)),
+                        matches => Err(format!(
+                            "`{}` matches {} sessions, use a longer prefix",
+                            prefix,
+                            matches.len()
+                        )),
+                    }
+                };
+                match session {
+                    // The permalink takes another Slack call, so it is looked up with the answer
+                    Ok(session) => {
+                        let slack = slack.clone();
+                        tokio::spawn(async move {
+                            let permalink = match session.slack_thread {
+                                Some(ref thread) => slack
+                                    .thread_permalink(thread)
+                                    .await
+                                    .inspect_err(|e| tracing::warn!("Failed to get permalink: {}", e))
+                                    .ok(),
+                                None => None,
+                            };
+                            let content = blocks::session_details(&session, permalink.as_deref());
+                            respond_to_command(&slack, &response_url, content).await;
+                        });
+                        return None;
+                    }
+                    Err(message) => SlackMessageContent::new().with_text(message),
+                }
+            }
+            SlashCommand::Run { alias, prompt } => match config.repo_path(&alias) {
+                Some(repo_path) => {
+                    let mut manager = session_manager.write().await;
+                    let session = manager.create_session(repo_path, Some(alias.clone()), prompt);
+                    match launcher.launch(&session) {
+                        Ok(()) => {
+                            let text = format!(
+                                "🚀 Started Claude Code in *{}* as `{}`",
+                                alias,
+                                &session.id.to_string()[..8]
+                            );
+                            launched = Some(session);
+                            SlackMessageContent::new().with_text(text)
+                        }
+                        Err(e) => {
+                            manager.remove_session(&session.id);
+                            SlackMessageContent::new()
+                                .with_text(format!("⚠️ Could not start Claude Code: {:#}", e))
+                        }
+                    }
+                }
+                None if config.repos.is_empty() => SlackMessageContent::new().with_text(
+                    "No repos are set up for `/claude run`, add them under `[repos]` in the config"
+                        .to_string(),
+                ),
+                None => SlackMessageContent::new().with_text(format!(
+                    "Unknown repo `{}`, try one of: {}",
+                    alias,
+                    config
+                        .repos
+                        .keys()
+                        .map(|a| format!("`{}`", a))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            },
+            SlashCommand::Help => SlackMessageContent::new().with_text(USAGE.to_string()),
+        }
+    };
+
+    let slack = slack.clone();
+    tokio::spawn(async move { respond_to_command(&slack, &response_url, content).await });
+    launched
+}
+
+async fn respond_to_command(
+    slack: &SlackService,
+    response_url: &SlackResponseUrl,
+    content: SlackMessageContent,
+) {
+    if let Err(e) = slack.respond_to_command(response_url, content).await {
+        tracing::warn!("Failed to answer slash command: {}", e);
+    }
+}
+
+/// Start stopping a session's Claude Code, noting it in the session thread
+async fn stop_session(
+    session_id: Uuid,
+    requested_by: &str,
+    session_manager: &RwLock<SessionManager>,
+    outbox: Option<&OutboxSender>,
+    grace: Duration,
+    stop_tx: &mpsc::UnboundedSender<StopResult>,
+) {
+    let Some(session) = session_manager.read().await.get_session(&session_id).cloned() else {
+        tracing::warn!("Asked to stop unknown session {}", session_id);
+        return;
+    };
+
+    let notice = match (session.claude_pid, session.claude_started.clone()) {
+        _ if !session.is_active() => "This session has already ended".to_string(),
+        (Some(pid), Some(started)) => {
+            tracing::info!("Stopping session {} (PID {}) {}", session_id, pid, requested_by);
+            let stop_tx = stop_tx.clone();
+            tokio::spawn(async move {
+                let result = stop_process(pid, &started, grace).await.map_err(|e| e.to_string());
+                let _ = stop_tx.send((session_id, result));
+            });
+            format!("⏹️ Stopping Claude Code, requested {}", requested_by)
+        }
+        _ => "⚠️ Claude Code's process isn't known for this session, stop it at its terminal"
+            .to_string(),
+    };
+
+    if let Some(outbox) = outbox {
+        outbox.send(
+            session_id,
+            session.slack_thread.as_ref(),
+            [OutboxMessage::reply_text(notice)],
+        );
+    }
+}
+
+/// Apply Approve/Deny button presses to the permission requests they belong to,
+/// returning the sessions a "Stop agent" button was pressed for
+async fn handle_block_actions(
+    event: SlackInteractionBlockActionsEvent,
+    user_id: &str,
+    session_manager: &RwLock<SessionManager>,
+    pending: &mut PendingRequests,
+    slack: Option<&Arc<SlackService>>,
+    notifiers: &mut Notifiers,
+    event_tx: &broadcast::Sender<DaemonEvent>,
+) -> Vec<Uuid> {
+    let mut stop_requests = Vec::new();
+
+    // Only the session owner may answer
+    if event.user.as_ref().is_none_or(|u| u.id.0 != user_id) {
+        return stop_requests;
+    }
+
+    for action in event.actions.unwrap_or_default() {
+        let (decision, outcome) = match action.action_id.0.as_str() {
+            STOP_SESSION_ACTION => {
+                stop_requests.extend(action.value.as_deref().and_then(|v| Uuid::parse_str(v).ok()));
+                continue;
+            }
+            PERMISSION_APPROVE_ACTION => (
+                PermissionDecision::Allow,
+                format!("✅ Approved by <@{}>", user_id),
+            ),
+            PERMISSION_DENY_ACTION => (
+                PermissionDecision::Deny,
+                format!("🚫 Denied by <@{}>", user_id),
+            ),
+            _ => continue,
+        };
+        let Some(request_id) = action.value.as_deref().and_then(|v| Uuid::parse_str(v).ok())
+        else {
+            continue;
+        };
+
+        match pending.resolve_permission(&request_id, decision) {
+            Some(resolved) => {
+                tracing::info!("Permission request {} answered: {:?}", request_id, decision);
+                finish_permission_request(
+                    resolved,
+                    decision,
+                    &outcome,
+                    session_manager,
+                    slack,
+                    notifiers,
+                    event_tx,
+                )
+                .await;
+            }
+            None => {
+                tracing::debug!("Permission request {} is no longer pending", request_id);
+            }
+        }
+    }
+
+    stop_requests
+}
+
+/// Record the outcome of a permission request in the session and its Slack message
+async fn finish_permission_request(
+    resolved: ResolvedPermission,
+    decision: PermissionDecision,
+    outcome: &str,
+    session_manager: &RwLock<SessionManager>,
+    slack: Option<&Arc<SlackService>>,
+    notifiers: &mut Notifiers,
+    event_tx: &broadcast::Sender<DaemonEvent>,
+) {
+    // Claude carries on once the tool is allowed or denied
+    let updated = if decision != PermissionDecision::Ask {
+        let mut manager = session_manager.write().await;
+        let updated = manager.set_status(
+            &resolved.session_id,
+            SessionStatus::Running,
+            match decision {
+                PermissionDecision::Allow => "Permission allowed",
+                _ => "Permission denied",
+            },
+        );
+        if let Some((ref session, _)) = updated {
+            let _ = event_tx.send(DaemonEvent::SessionUpdated(Box::new(session.clone())));
+        }
+        updated
+    } else {
+        None
+    };
+
+    if let Some(slack) = slack {
+        let (slack, outcome) = (slack.clone(), outcome.to_string());
+        tokio::spawn(async move {
+            if let Err(e) = slack
+                .resolve_permission_request(&resolved.message, &resolved.summary, &outcome)
+                .await
+            {
+                tracing::warn!("Failed to update permission request: {}", e);
+            }
+        });
+    }
+    if let Some((session, changed)) = updated {
+        notifiers.session_updated(SessionUpdate::quiet(session, changed));
+    }
+}
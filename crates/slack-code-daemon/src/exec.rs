@@ -0,0 +1,193 @@
+//! Runs a user-configured program for session events, e.g. for desktop
+//! notifications or sounds.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use slack_code_common::config::{ExecConfig, NotifyEvent};
+use slack_code_common::session::Session;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use crate::notify::{EventPayload, Notifier, SessionUpdate};
+
+/// Runs the command once per event, with a limit on runs at the same time
+pub struct ExecNotifier {
+    command: Vec<String>,
+    timeout: Duration,
+    permits: Arc<Semaphore>,
+}
+
+impl ExecNotifier {
+    pub fn new(config: &ExecConfig) -> Self {
+        Self {
+            command: config.command.clone(),
+            timeout: Duration::from_secs(config.timeout),
+            permits: Arc::new(Semaphore::new(config.concurrency)),
+        }
+    }
+
+    /// Start a run once a slot is free, without holding up later events
+    fn spawn(&self, event: NotifyEvent, update: &SessionUpdate) -> Result<()> {
+        let input = serde_json::to_vec(&EventPayload::new(event, update))?;
+        let env = environment(event, update)?;
+        let (name, command, timeout) = (self.name(), self.command.clone(), self.timeout);
+        let permits = self.permits.clone();
+
+        tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            if let Err(e) = run(&command, env, &input, timeout).await {
+                tracing::warn!("{} failed: {:#}", name, e);
+            }
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for ExecNotifier {
+    fn name(&self) -> String {
+        format!(
+            "Command {}",
+            self.command.first().map_or("", String::as_str)
+        )
+    }
+
+    async fn session_started(&self, session: &Session) -> Result<()> {
+        let update = SessionUpdate::new(session.clone(), true);
+        self.spawn(NotifyEvent::SessionStart, &update)
+    }
+
+    async fn status_changed(&self, update: &SessionUpdate) -> Result<()> {
+        self.spawn(NotifyEvent::StatusChange, update)
+    }
+
+    async fn session_ended(&self, update: &SessionUpdate) -> Result<()> {
+        self.spawn(NotifyEvent::SessionEnd, update)
+    }
+}
+
+/// `SLACK_CODE_*` variables describing the event
+fn environment(event: NotifyEvent, update: &SessionUpdate) -> Result<Vec<(&'static str, String)>> {
+    let session = &update.session;
+    let name = |value: serde_json::Value| value.as_str().unwrap_or_default().to_string();
+    Ok(vec![
+        ("SLACK_CODE_EVENT", name(serde_json::to_value(event)?)),
+        // Not SLACK_CODE_SESSION_ID, which would link a Claude Code the
+        // command starts to this session
+        ("SLACK_CODE_EVENT_SESSION_ID", session.id.to_string()),
+        (
+            "SLACK_CODE_STATUS",
+            name(serde_json::to_value(session.status.kind())?),
+        ),
+        (
+            "SLACK_CODE_STATUS_CHANGED",
+            update.status_changed.to_string(),
+        ),
+        ("SLACK_CODE_REPO", session.display_name()),
+        (
+            "SLACK_CODE_REPO_PATH",
+            session.repo_path.display().to_string(),
+        ),
+        (
+            "SLACK_CODE_BRANCH",
+            session.git_branch.clone().unwrap_or_default(),
+        ),
+        ("SLACK_CODE_PROMPT", session.prompt.clone()),
+    ])
+}
+
+/// Run the command with `input` on stdin, logging what it prints. It is
+/// killed if it runs longer than `timeout`.
+async fn run(
+    command: &[String],
+    env: Vec<(&'static str, String)>,
+    input: &[u8],
+    timeout: Duration,
+) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("No command configured");
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Could not run {}", program))?;
+    let mut stdin = child.stdin.take().context("No stdin")?;
+
+    let output = tokio::time::timeout(timeout, async move {
+        // Programs that ignore stdin may exit before reading it
+        let _ = stdin.write_all(input).await;
+        drop(stdin);
+        child.wait_with_output().await
+    })
+    .await
+    .with_context(|| format!("Killed after {:?}", timeout))??;
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        tracing::info!("{}: {}", program, line);
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        tracing::warn!("{}: {}", program, line);
+    }
+    if !output.status.success() {
+        bail!("{} {}", program, output.status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn shell(script: &str, arg: &str) -> Vec<String> {
+        ["sh", "-c", script, arg].map(String::from).to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let dir = std::env::temp_dir().join(format!("slack-code-exec-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut session = Session::new(PathBuf::from("/tmp/repo"), None, "Fix it".to_string());
+        session.repo_alias = Some("api".to_string());
+        let update = SessionUpdate::new(session.clone(), true);
+        let event = NotifyEvent::SessionEnd;
+        let input = serde_json::to_vec(&EventPayload::new(event, &update)).unwrap();
+
+        // The event arrives on stdin and in the environment
+        let script = r#"cat > "$0/event.json"; echo "$SLACK_CODE_EVENT $SLACK_CODE_EVENT_SESSION_ID $SLACK_CODE_REPO $SLACK_CODE_PROMPT" > "$0/env""#;
+        let command = shell(script, dir.to_str().unwrap());
+        let env = environment(event, &update).unwrap();
+        run(&command, env, &input, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let payload: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("event.json")).unwrap())
+                .unwrap();
+        assert_eq!(payload["event"], "session_end");
+        assert_eq!(payload["session"]["id"], session.id.to_string());
+        let env = std::fs::read_to_string(dir.join("env")).unwrap();
+        assert_eq!(env.trim(), format!("session_end {} api Fix it", session.id));
+
+        // Failures and slow programs are reported
+        let (failing, slow) = (shell("exit 2", "sh"), shell("sleep 30", "sh"));
+        let env = environment(event, &update).unwrap();
+        let failed = run(&failing, env, &input, Duration::from_secs(5)).await;
+        assert!(failed.is_err());
+        let env = environment(event, &update).unwrap();
+        let killed = run(&slow, env, &input, Duration::from_millis(200)).await;
+        assert!(format!("{:#}", killed.unwrap_err()).starts_with("Killed after"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+use crate::session::SessionManager;
+use crate::slack::SlackService;
+use slack_code_common::ipc::DaemonEvent;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::{broadcast, Notify, RwLock};
+
+/// How long to let session updates pile up before publishing once
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Keeps the owner's App Home tab in sync with the sessions
+pub struct HomePublisher {
+    slack: Arc<SlackService>,
+    session_manager: Arc<RwLock<SessionManager>>,
+    events: broadcast::Receiver<DaemonEvent>,
+    /// Publish now, e.g. when the owner opens the tab
+    refresh: Arc<Notify>,
+}
+
+impl HomePublisher {
+    pub fn new(
+        slack: Arc<SlackService>,
+        session_manager: Arc<RwLock<SessionManager>>,
+        events: broadcast::Receiver<DaemonEvent>,
+    ) -> (Self, Arc<Notify>) {
+        let refresh = Arc::new(Notify::new());
+        let publisher = Self {
+            slack,
+            session_manager,
+            events,
+            refresh: refresh.clone(),
+        };
+        (publisher, refresh)
+    }
+
+    /// Publish once at startup, then after every session update
+    pub async fn run(mut self) {
+        // Only warn about the first failure in a row (e.g. the Home tab is not enabled)
+        let mut failing = false;
+
+        loop {
+            let sessions = self.session_manager.read().await.get_sessions();
+            match self.slack.publish_home(&sessions).await {
+                Ok(()) => failing = false,
+                Err(e) if failing => tracing::debug!("Failed to publish App Home: {}", e),
+                Err(e) => {
+                    tracing::warn!("Failed to publish App Home: {}", e);
+                    failing = true;
+                }
+            }
+
+            if !self.wait_for_update().await {
+                break;
+            }
+
+            // Coalesce bursts of updates into one publish
+            tokio::time::sleep(DEBOUNCE).await;
+            while matches!(self.events.try_recv(), Ok(_) | Err(TryRecvError::Lagged(_))) {}
+        }
+    }
+
+    /// Wait for a session change or a refresh request; false once the daemon is gone
+    async fn wait_for_update(&mut self) -> bool {
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(DaemonEvent::SessionUpdated(_) | DaemonEvent::SessionRemoved(_)) => return true,
+                    Err(RecvError::Lagged(_)) => return true,
+                    Ok(_) => continue,
+                    Err(RecvError::Closed) => return false,
+                },
+                _ = self.refresh.notified() => return true,
+            }
+        }
+    }
+}
@@ -1,16 +1,23 @@
 use anyhow::Result;
-use slack_code_common::ipc::{DaemonCommand, DaemonEvent, HookEvent};
+use slack_code_common::ipc::{DaemonCommand, DaemonEvent, HookEvent, HookResponse};
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use tokio::net::UnixListener;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+/// A hook event, with a reply channel if the hook is waiting for a response
+#[derive(Debug)]
+pub struct HookMessage {
+    pub event: HookEvent,
+    pub reply_tx: Option<oneshot::Sender<HookResponse>>,
+}
 
 /// IPC Server for handling connections from hooks and TUI clients
 pub struct IpcServer {
     socket_path: PathBuf,
     /// Channel for sending events from hooks to session manager
-    hook_tx: mpsc::Sender<HookEvent>,
+    hook_tx: mpsc::Sender<HookMessage>,
     /// Channel for sending commands from TUI to daemon
     command_tx: mpsc::Sender<DaemonCommand>,
     /// Broadcast channel for sending events to all TUI subscribers
@@ -20,7 +27,7 @@ pub struct IpcServer {
 impl IpcServer {
     pub fn new(
         socket_path: PathBuf,
-        hook_tx: mpsc::Sender<HookEvent>,
+        hook_tx: mpsc::Sender<HookMessage>,
         command_tx: mpsc::Sender<DaemonCommand>,
         event_tx: broadcast::Sender<DaemonEvent>,
     ) -> Self {
@@ -83,7 +90,7 @@ impl IpcServer {
 
     async fn handle_connection(
         mut stream: UnixStream,
-        hook_tx: mpsc::Sender<HookEvent>,
+        hook_tx: mpsc::Sender<HookMessage>,
         command_tx: mpsc::Sender<DaemonCommand>,
         event_tx: broadcast::Sender<DaemonEvent>,
     ) -> Result<()> {
@@ -98,7 +105,27 @@ impl IpcServer {
         // Try to parse as HookEvent first (from hook binary)
         if let Ok(event) = serde_json::from_str::<HookEvent>(&msg) {
             tracing::debug!("Received hook event: {:?}", event);
-            hook_tx.send(event).await?;
+
+            let Some(timeout) = event.response_timeout() else {
+                hook_tx.send(HookMessage { event, reply_tx: None }).await?;
+                return Ok(());
+            };
+
+            // Blocking hook: hold the connection open until the daemon answers
+            let (reply_tx, reply_rx) = oneshot::channel();
+            hook_tx
+                .send(HookMessage {
+                    event,
+                    reply_tx: Some(reply_tx),
+                })
+                .await?;
+
+            let response = match tokio::time::timeout(timeout, reply_rx).await {
+                Ok(Ok(response)) => response,
+                // Timed out, or the daemon dropped the request
+                _ => HookResponse::NoReply,
+            };
+            write_message(&mut stream, &response)?;
             return Ok(());
         }
 
@@ -191,6 +218,13 @@ impl IpcClient {
         Ok(())
     }
 
+    /// Send a hook event that does not wait for an answer
+    pub fn send_hook_event(&self, event: &HookEvent) -> Result<()> {
+        let mut stream = self.connect()?;
+        write_message(&mut stream, event)?;
+        Ok(())
+    }
+
     /// Subscribe to daemon events
     pub fn subscribe(&self) -> Result<EventSubscription> {
         let mut stream = self.connect()?;
@@ -0,0 +1,121 @@
+//! Starts headless Claude Code for sessions created from Slack.
+
+use anyhow::{bail, Context, Result};
+use slack_code_common::ipc::SESSION_ID_ENV;
+use slack_code_common::session::Session;
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// How a launched Claude Code process ended: `Err` carries the reason it failed
+pub type LaunchExit = (Uuid, Result<(), String>);
+
+/// Longest stderr line kept as the failure reason
+const MAX_REASON_LEN: usize = 300;
+
+/// Runs the configured launch command and reports when each process exits
+pub struct Launcher {
+    command: Vec<String>,
+    exit_tx: mpsc::UnboundedSender<LaunchExit>,
+}
+
+impl Launcher {
+    pub fn new(command: Vec<String>, exit_tx: mpsc::UnboundedSender<LaunchExit>) -> Self {
+        Self { command, exit_tx }
+    }
+
+    /// Start Claude Code in the session's repository with its prompt. The
+    /// session ID is passed on in the environment so the SessionStart hook
+    /// can link Claude's session to ours.
+    pub fn launch(&self, session: &Session) -> Result<()> {
+        let Some((program, args)) = self.command.split_first() else {
+            bail!("No launch command configured");
+        };
+
+        let child = Command::new(program)
+            .args(args)
+            // Keep a prompt starting with `-` from being read as an option
+            .arg("--")
+            .arg(&session.prompt)
+            .current_dir(&session.repo_path)
+            .env(SESSION_ID_ENV, session.id.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not run {}", program))?;
+        tracing::info!(
+            "Launched Claude Code for session {} in {}",
+            session.id,
+            session.repo_path.display()
+        );
+
+        let session_id = session.id;
+        let exit_tx = self.exit_tx.clone();
+        tokio::spawn(async move {
+            let result = match child.wait_with_output().await {
+                Ok(output) if output.status.success() => Ok(()),
+                Ok(output) => Err(failure_reason(&output.status, &output.stderr)),
+                Err(e) => Err(format!("Lost track of Claude Code: {}", e)),
+            };
+            let _ = exit_tx.send((session_id, result));
+        });
+        Ok(())
+    }
+}
+
+/// The last line Claude Code wrote to stderr, or else its exit status
+fn failure_reason(status: &std::process::ExitStatus, stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    match stderr.lines().rev().map(str::trim).find(|l| !l.is_empty()) {
+        Some(line) => line.chars().take(MAX_REASON_LEN).collect(),
+        None => format!("Claude Code {}", status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn test_launch() {
+        let dir = std::env::temp_dir().join(format!("slack-code-launch-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let (exit_tx, mut exit_rx) = mpsc::unbounded_channel();
+
+        // The stub records where it ran, the session ID and its arguments
+        let stub =
+            r#"echo "$PWD $SLACK_CODE_SESSION_ID $*" > launched; echo "no credit" >&2; exit 3"#;
+        let launcher = Launcher::new(
+            vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                stub.to_string(),
+                "sh".to_string(),
+            ],
+            exit_tx,
+        );
+        let session = Session::new(dir.clone(), None, "-v is broken".to_string());
+        launcher.launch(&session).unwrap();
+
+        let (session_id, result) = exit_rx.recv().await.unwrap();
+        assert_eq!(session_id, session.id);
+        assert_eq!(result, Err("no credit".to_string()));
+        let launched = std::fs::read_to_string(dir.join("launched")).unwrap();
+        assert_eq!(
+            launched.trim(),
+            format!("{} {} -- -v is broken", dir.display(), session.id)
+        );
+
+        let missing = Launcher::new(
+            vec!["/nonexistent/claude".to_string()],
+            mpsc::unbounded_channel().0,
+        );
+        let session = Session::new(PathBuf::from("/tmp"), None, "Hi".to_string());
+        assert!(missing.launch(&session).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
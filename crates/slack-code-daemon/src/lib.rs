@@ -1,6 +1,20 @@
+pub mod blocks;
+pub mod check;
+pub mod commands;
 pub mod daemon;
+pub mod exec;
+pub mod home;
 pub mod ipc;
+pub mod launch;
+pub mod notify;
+pub mod outbox;
+pub mod pending;
 pub mod session;
+pub mod settle;
 pub mod slack;
+pub mod socket_mode;
+pub mod stop;
+pub mod transcript;
+pub mod webhook;
 
 pub use daemon::Daemon;
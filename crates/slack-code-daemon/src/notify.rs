@@ -0,0 +1,336 @@
+//! Notification backends.
+//!
+//! The daemon reports every session update to [`Notifiers`], which turns it
+//! into a lifecycle event and hands it to each enabled backend. Every backend
+//! has its own queue, so a slow one doesn't hold up the others or the daemon.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use slack_code_common::config::NotifyEvent;
+use slack_code_common::session::Session;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+use crate::outbox::OutboxSender;
+use crate::session::SessionManager;
+use crate::settle::SettleWindow;
+use crate::slack::SlackService;
+
+/// Version of the JSON sent to webhooks and commands. New fields can appear
+/// without a version bump; renaming or removing one bumps it.
+pub const PAYLOAD_VERSION: u32 = 1;
+
+/// A session event as JSON, for backends outside the daemon
+#[derive(Debug, Serialize)]
+pub struct EventPayload<'a> {
+    pub version: u32,
+    pub event: NotifyEvent,
+    pub timestamp: DateTime<Utc>,
+    /// Whether the session's status differs from the previous update
+    pub status_changed: bool,
+    pub session: &'a Session,
+}
+
+impl<'a> EventPayload<'a> {
+    pub fn new(event: NotifyEvent, update: &'a SessionUpdate) -> Self {
+        Self {
+            version: PAYLOAD_VERSION,
+            event,
+            timestamp: Utc::now(),
+            status_changed: update.status_changed,
+            session: &update.session,
+        }
+    }
+}
+
+/// A session update and what the daemon knows about it
+#[derive(Debug, Clone)]
+pub struct SessionUpdate {
+    pub session: Session,
+    /// Whether the session's status differs from before the update
+    pub status_changed: bool,
+    /// The user made the change, from Slack or the terminal, so Slack
+    /// doesn't reply about it
+    pub quiet: bool,
+    /// The Stop hook waits for a reply in the Slack thread
+    pub awaiting_reply: bool,
+    /// Slack asks with Approve/Deny buttons instead of a status reply
+    pub permission_prompt: bool,
+}
+
+impl SessionUpdate {
+    pub fn new(session: Session, status_changed: bool) -> Self {
+        Self {
+            session,
+            status_changed,
+            quiet: false,
+            awaiting_reply: false,
+            permission_prompt: false,
+        }
+    }
+
+    /// An update the user made, which Slack doesn't reply about
+    pub fn quiet(session: Session, status_changed: bool) -> Self {
+        Self {
+            quiet: true,
+            ..Self::new(session, status_changed)
+        }
+    }
+}
+
+/// A backend that hears about session lifecycle events
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> String;
+
+    /// A session was seen for the first time
+    async fn session_started(&self, session: &Session) -> Result<()>;
+
+    /// A running session was updated
+    async fn status_changed(&self, update: &SessionUpdate) -> Result<()>;
+
+    /// A session completed or failed
+    async fn session_ended(&self, update: &SessionUpdate) -> Result<()>;
+}
+
+/// Hands session updates to every enabled backend
+pub struct Notifiers {
+    backends: Vec<mpsc::UnboundedSender<(NotifyEvent, SessionUpdate)>>,
+    started: HashSet<Uuid>,
+}
+
+impl Notifiers {
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+            started: HashSet::new(),
+        }
+    }
+
+    /// Enable a backend for the given events
+    pub fn add(&mut self, notifier: Arc<dyn Notifier>, events: Vec<NotifyEvent>) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<(NotifyEvent, SessionUpdate)>();
+        tokio::spawn(async move {
+            while let Some((event, update)) = rx.recv().await {
+                if !events.contains(&event) {
+                    continue;
+                }
+                let result = match event {
+                    NotifyEvent::SessionStart => notifier.session_started(&update.session).await,
+                    NotifyEvent::StatusChange => notifier.status_changed(&update).await,
+                    NotifyEvent::SessionEnd => notifier.session_ended(&update).await,
+                };
+                if let Err(e) = result {
+                    tracing::warn!("{} notification failed: {:#}", notifier.name(), e);
+                }
+            }
+        });
+        self.backends.push(tx);
+    }
+
+    /// Report a session update. The first one for a session starts it, and
+    /// one that leaves it completed or failed ends it. A session that is
+    /// updated again after it ended starts over.
+    pub fn session_updated(&mut self, update: SessionUpdate) {
+        let event = if self.started.insert(update.session.id) {
+            NotifyEvent::SessionStart
+        } else if update.status_changed && !update.session.is_active() {
+            self.started.remove(&update.session.id);
+            NotifyEvent::SessionEnd
+        } else {
+            NotifyEvent::StatusChange
+        };
+        for backend in &self.backends {
+            let _ = backend.send((event, update.clone()));
+        }
+    }
+}
+
+impl Default for Notifiers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Posts sessions to their Slack threads through the outbox
+pub struct SlackNotifier {
+    slack: Arc<SlackService>,
+    outbox: OutboxSender,
+    session_manager: Arc<RwLock<SessionManager>>,
+    settle: Mutex<SettleWindow>,
+}
+
+impl SlackNotifier {
+    pub fn new(
+        slack: Arc<SlackService>,
+        outbox: OutboxSender,
+        session_manager: Arc<RwLock<SessionManager>>,
+        settle_window: Duration,
+    ) -> Self {
+        Self {
+            slack,
+            outbox,
+            session_manager,
+            settle: Mutex::new(SettleWindow::new(settle_window)),
+        }
+    }
+
+    /// Release settled replies, and sum up what quiet hours held back once
+    /// they are over
+    pub async fn run(self: Arc<Self>) {
+        let mut settle_tick = tokio::time::interval(Duration::from_secs(1));
+        let mut catch_up_tick = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            tokio::select! {
+                _ = settle_tick.tick() => {
+                    let due = self.settle.lock().unwrap().due(Instant::now());
+                    for (session_id, messages) in due {
+                        let thread = self
+                            .session_manager
+                            .read()
+                            .await
+                            .get_session(&session_id)
+                            .and_then(|s| s.slack_thread.clone());
+                        self.outbox.send(session_id, thread.as_ref(), messages);
+                    }
+                }
+                _ = catch_up_tick.tick() => {
+                    if let Some(summary) = self.slack.catch_up(chrono::Utc::now()) {
+                        self.outbox.send(Uuid::nil(), None, [summary]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Refresh the root message and reply in the thread, holding back replies
+    /// the settle window asks for
+    fn post_update(&self, update: &SessionUpdate) {
+        let session = &update.session;
+        // The permission prompt replaces the status reply, and needs an answer now
+        let (status_changed, urgent) = if update.permission_prompt {
+            (false, true)
+        } else {
+            (
+                update.status_changed && !update.quiet,
+                update.awaiting_reply,
+            )
+        };
+        let messages = self
+            .slack
+            .status_messages(session, status_changed, update.awaiting_reply);
+        let messages =
+            self.settle
+                .lock()
+                .unwrap()
+                .filter(session, messages, urgent, Instant::now());
+        self.outbox
+            .send(session.id, session.slack_thread.as_ref(), messages);
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> String {
+        "Slack".to_string()
+    }
+
+    async fn session_started(&self, session: &Session) -> Result<()> {
+        // A resumed session carries on in its thread
+        if session.slack_thread.is_some() {
+            self.post_update(&SessionUpdate::new(session.clone(), true));
+            return Ok(());
+        }
+        // Later messages queue up behind the root message until it is posted
+        self.outbox
+            .send(session.id, None, self.slack.start_messages(session));
+        Ok(())
+    }
+
+    async fn status_changed(&self, update: &SessionUpdate) -> Result<()> {
+        self.post_update(update);
+        Ok(())
+    }
+
+    async fn session_ended(&self, update: &SessionUpdate) -> Result<()> {
+        self.post_update(update);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slack_code_common::session::SessionStatus;
+    use std::path::PathBuf;
+
+    /// Records the events it hears about
+    struct Recorder(mpsc::UnboundedSender<(&'static str, SessionStatus)>);
+
+    #[async_trait]
+    impl Notifier for Recorder {
+        fn name(&self) -> String {
+            "recorder".to_string()
+        }
+
+        async fn session_started(&self, session: &Session) -> Result<()> {
+            let _ = self.0.send(("start", session.status.clone()));
+            Ok(())
+        }
+
+        async fn status_changed(&self, update: &SessionUpdate) -> Result<()> {
+            let _ = self.0.send(("status", update.session.status.clone()));
+            Ok(())
+        }
+
+        async fn session_ended(&self, update: &SessionUpdate) -> Result<()> {
+            let _ = self.0.send(("end", update.session.status.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifiers() {
+        let (all_tx, mut all_rx) = mpsc::unbounded_channel();
+        let (ends_tx, mut ends_rx) = mpsc::unbounded_channel();
+        let mut notifiers = Notifiers::new();
+        notifiers.add(
+            Arc::new(Recorder(all_tx)),
+            vec![
+                NotifyEvent::SessionStart,
+                NotifyEvent::StatusChange,
+                NotifyEvent::SessionEnd,
+            ],
+        );
+        notifiers.add(Arc::new(Recorder(ends_tx)), vec![NotifyEvent::SessionEnd]);
+
+        let mut session = Session::new(PathBuf::from("/tmp/repo"), None, "test".to_string());
+        session.status = SessionStatus::Running;
+        notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+        notifiers.session_updated(SessionUpdate::new(session.clone(), false));
+        session.status = SessionStatus::Completed;
+        notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+        // Resumed after it ended
+        session.status = SessionStatus::Running;
+        notifiers.session_updated(SessionUpdate::new(session.clone(), true));
+
+        for expected in [
+            ("start", SessionStatus::Running),
+            ("status", SessionStatus::Running),
+            ("end", SessionStatus::Completed),
+            ("start", SessionStatus::Running),
+        ] {
+            assert_eq!(all_rx.recv().await.unwrap(), expected);
+        }
+        assert_eq!(
+            ends_rx.recv().await.unwrap(),
+            ("end", SessionStatus::Completed)
+        );
+    }
+}
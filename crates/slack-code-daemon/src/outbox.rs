@@ -0,0 +1,614 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use slack_code_common::session::SlackThread;
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::slack::SlackService;
+
+/// Delay before the first retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Upper bound for the retry delay
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Attempts before a message is given up on
+const MAX_ATTEMPTS: u32 = 12;
+
+/// How long a posted thread is remembered for messages queued before the
+/// daemon heard about it
+const THREAD_MEMORY: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Slack error codes worth retrying; any other API error is permanent
+const TRANSIENT_API_ERRORS: &[&str] = &[
+    "internal_error",
+    "fatal_error",
+    "service_unavailable",
+    "request_timeout",
+];
+
+/// A Slack message waiting to be delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutboxMessage {
+    /// Post a session's root message in `channel`, or the owner's DMs
+    StartThread {
+        channel: Option<String>,
+        content: SlackMessageContent,
+    },
+
+    /// Replace the session's root message
+    UpdateRoot { content: SlackMessageContent },
+
+    /// Reply in the session thread
+    Reply { content: SlackMessageContent },
+
+    /// Post to the owner's DMs, outside any session thread
+    Direct { content: SlackMessageContent },
+
+    /// Show the session status as the only status reaction on the root message
+    SetReaction { name: String },
+
+    /// Upload a text file to the session thread
+    UploadFile {
+        filename: String,
+        title: String,
+        content: String,
+    },
+}
+
+impl OutboxMessage {
+    /// A plain text reply in the session thread
+    pub fn reply_text(text: impl Into<String>) -> Self {
+        OutboxMessage::Reply {
+            content: SlackMessageContent::new().with_text(text.into()),
+        }
+    }
+
+    /// Whether the message goes to the session thread, so it has to wait
+    /// for the root message
+    pub fn needs_thread(&self) -> bool {
+        !matches!(
+            self,
+            OutboxMessage::StartThread { .. } | OutboxMessage::Direct { .. }
+        )
+    }
+}
+
+/// A queued message and its delivery state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// Thread the message belongs to; filled in once the root message is posted
+    pub thread: Option<SlackThread>,
+    pub message: OutboxMessage,
+    pub attempts: u32,
+    pub not_before: DateTime<Utc>,
+}
+
+/// Why a delivery failed, and what to do about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// HTTP 429 or `ratelimited`; wait as long as Slack asks
+    RateLimited(Option<Duration>),
+
+    /// Network trouble or a Slack outage; retry with backoff
+    Transient,
+
+    /// Retrying would not help (bad token, unknown channel, ...)
+    Permanent,
+}
+
+impl Failure {
+    /// Classify an error returned by the Slack client
+    pub fn classify(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<SlackClientError>() {
+            Some(SlackClientError::RateLimitError(e)) => Failure::RateLimited(e.retry_after),
+            Some(SlackClientError::ApiError(e))
+                if TRANSIENT_API_ERRORS.contains(&e.code.as_str()) =>
+            {
+                Failure::Transient
+            }
+            Some(SlackClientError::HttpError(e)) if e.status_code.is_client_error() => {
+                Failure::Permanent
+            }
+            Some(SlackClientError::ApiError(_)) | Some(SlackClientError::ProtocolError(_)) => {
+                Failure::Permanent
+            }
+            _ => Failure::Transient,
+        }
+    }
+}
+
+/// Retry delay after the given number of failed attempts
+fn backoff(attempts: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Durable queue of Slack messages.
+/// Messages of one session go out strictly in order; sessions don't hold each other up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    entries: VecDeque<OutboxEntry>,
+
+    /// Slack asked us to back off until then (Retry-After)
+    #[serde(default)]
+    paused_until: Option<DateTime<Utc>>,
+
+    /// Threads recently posted, and when
+    #[serde(skip)]
+    threads: HashMap<Uuid, (SlackThread, DateTime<Utc>)>,
+
+    /// Root messages given up on, posted again when their session has more to say
+    #[serde(default)]
+    lost_roots: HashMap<Uuid, OutboxMessage>,
+}
+
+impl Outbox {
+    /// Load the queue left by a previous run
+    pub fn load(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the queue to disk, replacing the previous copy atomically
+    pub fn save(&self, path: &PathBuf) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Queue a message. One sent before the daemon heard about its session's
+    /// thread gets the thread from the root message, once that is posted. If
+    /// the root message was given up on, it is queued again first.
+    /// Returns false if the message was dropped because no root message was
+    /// queued, posted or given up on for it.
+    pub fn push(&mut self, mut entry: OutboxEntry) -> bool {
+        if matches!(entry.message, OutboxMessage::StartThread { .. }) {
+            self.lost_roots.remove(&entry.session_id);
+        } else if entry.thread.is_none() && entry.message.needs_thread() {
+            entry.thread = self
+                .threads
+                .get(&entry.session_id)
+                .map(|(thread, _)| thread.clone());
+            let root_queued = self.entries.iter().any(|e| {
+                e.session_id == entry.session_id
+                    && matches!(e.message, OutboxMessage::StartThread { .. })
+            });
+            if entry.thread.is_none() && !root_queued {
+                let Some(root) = self.lost_roots.remove(&entry.session_id) else {
+                    return false;
+                };
+                self.entries.push_back(OutboxEntry {
+                    id: Uuid::new_v4(),
+                    session_id: entry.session_id,
+                    thread: None,
+                    message: root,
+                    attempts: 0,
+                    not_before: entry.not_before,
+                });
+            }
+        }
+        self.entries.push_back(entry);
+        true
+    }
+
+    /// Heads of each session's queue, oldest first, leaving out messages
+    /// still waiting for their thread
+    fn heads(&self) -> impl Iterator<Item = &OutboxEntry> {
+        let mut seen = HashSet::new();
+        self.entries
+            .iter()
+            .filter(move |entry| seen.insert(entry.session_id))
+            .filter(|entry| entry.thread.is_some() || !entry.message.needs_thread())
+    }
+
+    /// The next message that may be sent now
+    pub fn next_ready(&self, now: DateTime<Utc>) -> Option<OutboxEntry> {
+        if self.paused_until.is_some_and(|until| until > now) {
+            return None;
+        }
+        self.heads().find(|entry| entry.not_before <= now).cloned()
+    }
+
+    /// When the next message becomes ready, if any can be sent
+    pub fn next_wakeup(&self) -> Option<DateTime<Utc>> {
+        let earliest = self.heads().map(|entry| entry.not_before).min()?;
+        Some(self.paused_until.map_or(earliest, |until| until.max(earliest)))
+    }
+
+    /// Remove a delivered message. A newly created thread is handed to the
+    /// session's remaining messages, and to ones queued until the daemon
+    /// knows about it.
+    pub fn complete(&mut self, id: &Uuid, created_thread: Option<&SlackThread>) {
+        let Some(index) = self.entries.iter().position(|e| e.id == *id) else {
+            return;
+        };
+        let entry = self.entries.remove(index).unwrap();
+
+        if let Some(thread) = created_thread {
+            let now = Utc::now();
+            self.threads
+                .retain(|_, (_, posted)| now - *posted < THREAD_MEMORY);
+            self.threads.insert(entry.session_id, (thread.clone(), now));
+            for other in self
+                .entries
+                .iter_mut()
+                .filter(|e| e.session_id == entry.session_id && e.thread.is_none())
+            {
+                other.thread = Some(thread.clone());
+            }
+        }
+    }
+
+    /// Put a message off until `until` without counting it as an attempt
+    pub fn defer(&mut self, id: &Uuid, until: DateTime<Utc>) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == *id) {
+            entry.not_before = until;
+        }
+    }
+
+    /// Record a failed attempt. Returns false once the message has been given up on.
+    pub fn fail(&mut self, id: &Uuid, failure: &Failure, now: DateTime<Utc>) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.id == *id) else {
+            return false;
+        };
+        entry.attempts += 1;
+
+        let delay = match failure {
+            Failure::Permanent => None,
+            _ if entry.attempts >= MAX_ATTEMPTS => None,
+            Failure::RateLimited(retry_after) => {
+                let delay = retry_after.unwrap_or_else(|| backoff(entry.attempts));
+                self.paused_until = Some(now + delay);
+                Some(delay)
+            }
+            Failure::Transient => Some(backoff(entry.attempts)),
+        };
+
+        match delay {
+            Some(delay) => {
+                entry.not_before = now + delay;
+                true
+            }
+            None => {
+                self.discard(id);
+                false
+            }
+        }
+    }
+
+    /// Drop a message. Without its root message a session's other messages
+    /// have nowhere to go, so they are dropped too; the root message is kept
+    /// to try again with the session's next message.
+    fn discard(&mut self, id: &Uuid) {
+        let Some(index) = self.entries.iter().position(|e| e.id == *id) else {
+            return;
+        };
+        let entry = self.entries.remove(index).unwrap();
+        if matches!(entry.message, OutboxMessage::StartThread { .. }) {
+            self.entries
+                .retain(|e| e.session_id != entry.session_id || e.thread.is_some());
+            self.lost_roots.insert(entry.session_id, entry.message);
+        }
+    }
+}
+
+/// Queues messages for the outbox worker
+#[derive(Clone)]
+pub struct OutboxSender {
+    tx: mpsc::UnboundedSender<OutboxEntry>,
+}
+
+impl OutboxSender {
+    /// Queue messages for a session, in order. Without a thread they wait for
+    /// the session's root message to be posted.
+    pub fn send(
+        &self,
+        session_id: Uuid,
+        thread: Option<&SlackThread>,
+        messages: impl IntoIterator<Item = OutboxMessage>,
+    ) {
+        for message in messages {
+            let _ = self.tx.send(OutboxEntry {
+                id: Uuid::new_v4(),
+                session_id,
+                thread: thread.cloned(),
+                message,
+                attempts: 0,
+                not_before: Utc::now(),
+            });
+        }
+    }
+}
+
+/// Delivers queued messages, persisting the queue after every change
+pub struct OutboxWorker {
+    outbox: Outbox,
+    path: PathBuf,
+    slack: Arc<SlackService>,
+    rx: mpsc::UnboundedReceiver<OutboxEntry>,
+    /// Root messages posted for sessions, reported back to the daemon
+    thread_tx: mpsc::UnboundedSender<(Uuid, SlackThread)>,
+}
+
+impl OutboxWorker {
+    /// Create a worker, picking up messages a previous run did not deliver
+    pub fn new(
+        path: PathBuf,
+        slack: Arc<SlackService>,
+        thread_tx: mpsc::UnboundedSender<(Uuid, SlackThread)>,
+    ) -> (Self, OutboxSender) {
+        let outbox = Outbox::load(&path).unwrap_or_else(|e| {
+            tracing::warn!("Could not read outbox {}: {}", path.display(), e);
+            Outbox::default()
+        });
+        if !outbox.is_empty() {
+            tracing::info!("Resuming {} undelivered Slack messages", outbox.len());
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let worker = Self {
+            outbox,
+            path,
+            slack,
+            rx,
+            thread_tx,
+        };
+        (worker, OutboxSender { tx })
+    }
+
+    /// Deliver messages until every sender is dropped
+    pub async fn run(mut self) {
+        loop {
+            while let Some(entry) = self.outbox.next_ready(Utc::now()) {
+                self.deliver(entry).await;
+            }
+
+            let idle = match self.outbox.next_wakeup() {
+                Some(at) => (at - Utc::now()).to_std().unwrap_or_default(),
+                None => Duration::from_secs(3600),
+            };
+
+            tokio::select! {
+                entry = self.rx.recv() => match entry {
+                    Some(entry) => {
+                        self.push(entry);
+                        // Take everything already waiting before saving once
+                        while let Ok(entry) = self.rx.try_recv() {
+                            self.push(entry);
+                        }
+                        self.save();
+                    }
+                    None => break,
+                },
+                _ = tokio::time::sleep(idle) => {}
+            }
+        }
+    }
+
+    fn push(&mut self, entry: OutboxEntry) {
+        let session_id = entry.session_id;
+        if !self.outbox.push(entry) {
+            tracing::warn!(
+                "Dropping Slack message for session {}, which has no thread",
+                session_id
+            );
+        }
+    }
+
+    async fn deliver(&mut self, entry: OutboxEntry) {
+        // Quiet hours hold back anything that would notify
+        if let Some(until) = self.slack.held_until(&entry.message, Utc::now()) {
+            self.outbox.defer(&entry.id, until);
+            self.save();
+            return;
+        }
+
+        match self.slack.deliver(&entry.message, entry.thread.as_ref()).await {
+            Ok(created_thread) => {
+                self.outbox.complete(&entry.id, created_thread.as_ref());
+                if let Some(thread) = created_thread {
+                    let _ = self.thread_tx.send((entry.session_id, thread));
+                }
+            }
+            Err(e) => {
+                let failure = Failure::classify(&e);
+                if self.outbox.fail(&entry.id, &failure, Utc::now()) {
+                    tracing::warn!(
+                        "Slack message for session {} failed ({:?}), will retry: {}",
+                        entry.session_id,
+                        failure,
+                        e
+                    );
+                } else {
+                    tracing::error!(
+                        "Giving up on Slack message for session {} after {} attempts: {}",
+                        entry.session_id,
+                        entry.attempts + 1,
+                        e
+                    );
+                }
+            }
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Err(e) = self.outbox.save(&self.path) {
+            tracing::warn!("Failed to save outbox {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(session_id: Uuid, thread: Option<&SlackThread>, message: OutboxMessage) -> OutboxEntry {
+        OutboxEntry {
+            id: Uuid::new_v4(),
+            session_id,
+            thread: thread.cloned(),
+            message,
+            attempts: 0,
+            not_before: Utc::now(),
+        }
+    }
+
+    fn start() -> OutboxMessage {
+        OutboxMessage::StartThread {
+            channel: None,
+            content: SlackMessageContent::new().with_text("start".into()),
+        }
+    }
+
+    #[test]
+    fn test_keeps_session_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let thread = SlackThread {
+            channel_id: "D1".into(),
+            parent_ts: "1.0".into(),
+        };
+        let mut outbox = Outbox::default();
+        outbox.push(entry(a, None, start()));
+        outbox.push(entry(a, None, OutboxMessage::reply_text("a1")));
+        outbox.push(entry(b, Some(&thread), OutboxMessage::reply_text("b1")));
+
+        // A failing root message holds back its own session only
+        let now = Utc::now();
+        let first = outbox.next_ready(now).unwrap();
+        assert_eq!(first.session_id, a);
+        assert!(outbox.fail(&first.id, &Failure::Transient, now));
+        let next = outbox.next_ready(now).unwrap();
+        assert_eq!(next.session_id, b);
+        outbox.complete(&next.id, None);
+        assert!(outbox.next_ready(now).is_none());
+        assert_eq!(outbox.next_wakeup(), Some(now + backoff(1)));
+
+        // Once posted, the thread is handed to the next message
+        let later = now + backoff(1);
+        let first = outbox.next_ready(later).unwrap();
+        outbox.complete(&first.id, Some(&thread));
+        let reply = outbox.next_ready(later).unwrap();
+        assert_eq!(reply.thread.unwrap().parent_ts, "1.0");
+    }
+
+    #[test]
+    fn test_messages_queued_while_root_is_posted() {
+        let session = Uuid::new_v4();
+        let thread = SlackThread {
+            channel_id: "D1".into(),
+            parent_ts: "1.0".into(),
+        };
+        let mut outbox = Outbox::default();
+        assert!(outbox.push(entry(session, None, start())));
+        let root = outbox.next_ready(Utc::now()).unwrap();
+        outbox.complete(&root.id, Some(&thread));
+
+        // Sent before the daemon heard about the thread, still lands in it
+        assert!(outbox.push(entry(session, None, OutboxMessage::reply_text("late"))));
+        let reply = outbox.next_ready(Utc::now()).unwrap();
+        assert_eq!(reply.thread.unwrap().parent_ts, "1.0");
+
+        // Without any root message there is nowhere to post
+        let orphan = entry(Uuid::new_v4(), None, OutboxMessage::reply_text("lost"));
+        assert!(!outbox.push(orphan));
+        assert_eq!(outbox.len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limit_and_permanent_failures() {
+        let session = Uuid::new_v4();
+        let mut outbox = Outbox::default();
+        outbox.push(entry(session, None, start()));
+        outbox.push(entry(session, None, OutboxMessage::reply_text("queued")));
+        outbox.push(entry(Uuid::new_v4(), None, start()));
+
+        // Retry-After pauses every session
+        let now = Utc::now();
+        let first = outbox.next_ready(now).unwrap();
+        let failure = Failure::RateLimited(Some(Duration::from_secs(30)));
+        assert!(outbox.fail(&first.id, &failure, now));
+        assert!(outbox.next_ready(now + chrono::Duration::seconds(29)).is_none());
+        assert!(outbox.next_ready(now + chrono::Duration::seconds(30)).is_some());
+
+        // A root message that can never be posted takes its replies with it
+        assert!(!outbox.fail(&first.id, &Failure::Permanent, now));
+        assert_eq!(outbox.len(), 1);
+
+        // The session's next message tries the root message again
+        assert!(outbox.push(entry(session, None, OutboxMessage::reply_text("later"))));
+        assert_eq!(outbox.len(), 3);
+        let root = outbox
+            .heads()
+            .find(|e| e.session_id == session)
+            .cloned()
+            .unwrap();
+        assert!(matches!(root.message, OutboxMessage::StartThread { .. }));
+        let thread = SlackThread {
+            channel_id: "D1".into(),
+            parent_ts: "2.0".into(),
+        };
+        outbox.complete(&root.id, Some(&thread));
+        let reply = outbox
+            .heads()
+            .find(|e| e.session_id == session)
+            .cloned()
+            .unwrap();
+        assert_eq!(reply.thread.unwrap().parent_ts, "2.0");
+    }
+
+    #[test]
+    fn test_survives_restart() {
+        let path = std::env::temp_dir().join(format!("slack-code-outbox-{}.json", Uuid::new_v4()));
+        let thread = SlackThread {
+            channel_id: "D1".into(),
+            parent_ts: "1.0".into(),
+        };
+        let mut outbox = Outbox::default();
+        outbox.push(entry(Uuid::new_v4(), None, start()));
+        outbox.push(entry(Uuid::new_v4(), Some(&thread), OutboxMessage::reply_text("hi")));
+        outbox.save(&path).unwrap();
+
+        let restored = Outbox::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert!(matches!(
+            restored.entries[1].message,
+            OutboxMessage::Reply { .. }
+        ));
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(20), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,241 @@
+use slack_code_common::ipc::{HookResponse, PermissionDecision};
+use slack_code_common::session::SlackThread;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::slack::SlackMessageRef;
+
+/// A Stop hook blocked until the user replies in the session thread
+struct PendingReply {
+    session_id: Uuid,
+    reply_tx: oneshot::Sender<HookResponse>,
+}
+
+/// A permission request waiting for a button press in Slack
+struct PendingPermission {
+    session_id: Uuid,
+    message: SlackMessageRef,
+    summary: String,
+    deadline: Instant,
+    reply_tx: oneshot::Sender<HookResponse>,
+}
+
+/// A permission request that has been answered or timed out
+#[derive(Debug)]
+pub struct ResolvedPermission {
+    pub session_id: Uuid,
+    /// The Slack message holding the buttons
+    pub message: SlackMessageRef,
+    pub summary: String,
+}
+
+/// Hooks waiting on an answer from Slack
+pub struct PendingRequests {
+    /// Waiting Stop hooks keyed by (channel_id, parent_ts) of their thread
+    replies: HashMap<(String, String), PendingReply>,
+
+    /// Waiting permission hooks keyed by request ID (the button value)
+    permissions: HashMap<Uuid, PendingPermission>,
+}
+
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self {
+            replies: HashMap::new(),
+            permissions: HashMap::new(),
+        }
+    }
+
+    /// Wait for the next reply in a thread, replacing any earlier waiter
+    pub fn wait_for_reply(
+        &mut self,
+        thread: &SlackThread,
+        session_id: Uuid,
+        reply_tx: oneshot::Sender<HookResponse>,
+    ) {
+        self.replies.retain(|_, pending| !pending.reply_tx.is_closed());
+        self.replies.insert(
+            (thread.channel_id.clone(), thread.parent_ts.clone()),
+            PendingReply {
+                session_id,
+                reply_tx,
+            },
+        );
+    }
+
+    /// Hand a thread reply to the hook waiting on that thread.
+    /// Returns the session it was delivered to, or None if nothing was waiting.
+    pub fn deliver_reply(&mut self, channel_id: &str, thread_ts: &str, text: String) -> Option<Uuid> {
+        let pending = self
+            .replies
+            .remove(&(channel_id.to_string(), thread_ts.to_string()))?;
+
+        pending
+            .reply_tx
+            .send(HookResponse::Reply(text))
+            .ok()
+            .map(|_| pending.session_id)
+    }
+
+    /// Wait for a button press on a posted permission request
+    pub fn wait_for_permission(
+        &mut self,
+        request_id: Uuid,
+        session_id: Uuid,
+        message: SlackMessageRef,
+        summary: String,
+        timeout: Duration,
+        reply_tx: oneshot::Sender<HookResponse>,
+    ) {
+        self.permissions.insert(
+            request_id,
+            PendingPermission {
+                session_id,
+                message,
+                summary,
+                deadline: Instant::now() + timeout,
+                reply_tx,
+            },
+        );
+    }
+
+    /// Answer a permission request.
+    /// Returns None if the request is unknown, already answered, or the hook gave up.
+    pub fn resolve_permission(
+        &mut self,
+        request_id: &Uuid,
+        decision: PermissionDecision,
+    ) -> Option<ResolvedPermission> {
+        let pending = self.permissions.remove(request_id)?;
+
+        pending
+            .reply_tx
+            .send(HookResponse::Permission(decision))
+            .ok()?;
+
+        Some(ResolvedPermission {
+            session_id: pending.session_id,
+            message: pending.message,
+            summary: pending.summary,
+        })
+    }
+
+    /// Answer overdue permission requests with the fallback decision
+    pub fn expire_permissions(
+        &mut self,
+        now: Instant,
+        fallback: PermissionDecision,
+    ) -> Vec<ResolvedPermission> {
+        let expired: Vec<Uuid> = self
+            .permissions
+            .iter()
+            .filter(|(_, p)| p.deadline <= now || p.reply_tx.is_closed())
+            .map(|(id, _)| *id)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|id| self.permissions.remove(&id))
+            .map(|pending| {
+                let _ = pending.reply_tx.send(HookResponse::Permission(fallback));
+                ResolvedPermission {
+                    session_id: pending.session_id,
+                    message: pending.message,
+                    summary: pending.summary,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for PendingRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread() -> SlackThread {
+        SlackThread {
+            channel_id: "D123".to_string(),
+            parent_ts: "1700000000.000100".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deliver_reply() {
+        let mut pending = PendingRequests::new();
+        let session_id = Uuid::new_v4();
+        let (tx, mut rx) = oneshot::channel();
+        pending.wait_for_reply(&thread(), session_id, tx);
+
+        assert_eq!(pending.deliver_reply("D123", "1.0", "other".into()), None);
+        assert_eq!(
+            pending.deliver_reply("D123", "1700000000.000100", "run the tests".into()),
+            Some(session_id)
+        );
+        assert_eq!(rx.try_recv().unwrap(), HookResponse::Reply("run the tests".into()));
+
+        // Only the first reply is delivered
+        assert_eq!(
+            pending.deliver_reply("D123", "1700000000.000100", "again".into()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_permission_timeout_uses_fallback() {
+        let mut pending = PendingRequests::new();
+        let request_id = Uuid::new_v4();
+        let message = SlackMessageRef {
+            channel_id: "D123".to_string(),
+            ts: "1700000000.000200".to_string(),
+        };
+        let (tx, mut rx) = oneshot::channel();
+        pending.wait_for_permission(
+            request_id,
+            Uuid::new_v4(),
+            message.clone(),
+            "Bash".to_string(),
+            Duration::from_secs(60),
+            tx,
+        );
+
+        let now = Instant::now();
+        assert!(pending
+            .expire_permissions(now, PermissionDecision::Deny)
+            .is_empty());
+
+        let expired =
+            pending.expire_permissions(now + Duration::from_secs(61), PermissionDecision::Deny);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].message, message);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            HookResponse::Permission(PermissionDecision::Deny)
+        );
+
+        // A late button press finds nothing to answer
+        assert!(pending
+            .resolve_permission(&request_id, PermissionDecision::Allow)
+            .is_none());
+    }
+
+    #[test]
+    fn test_hook_gave_up() {
+        let mut pending = PendingRequests::new();
+        let (tx, rx) = oneshot::channel();
+        pending.wait_for_reply(&thread(), Uuid::new_v4(), tx);
+        drop(rx);
+
+        assert_eq!(
+            pending.deliver_reply("D123", "1700000000.000100", "too late".into()),
+            None
+        );
+    }
+}
@@ -2,7 +2,7 @@ use chrono::Utc;
 use slack_code_common::ipc::HookEvent;
 use slack_code_common::session::{Session, SessionStatus, SlackThread, WaitReason};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// Manages all Claude Code sessions
@@ -12,16 +12,35 @@ pub struct SessionManager {
 
     /// Mapping from Claude's session ID to our UUID
     claude_id_map: HashMap<String, Uuid>,
+
+    /// Registered repositories by alias, used to name external sessions
+    repos: Vec<(String, PathBuf)>,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
+        Self::with_repos(Vec::new())
+    }
+
+    /// Create a manager that names sessions after the registered repositories
+    pub fn with_repos(repos: Vec<(String, PathBuf)>) -> Self {
         Self {
             sessions: HashMap::new(),
             claude_id_map: HashMap::new(),
+            repos,
         }
     }
 
+    /// Alias of the registered repository containing `path`; the most
+    /// specific one wins when repositories are nested
+    fn alias_for(&self, path: &Path) -> Option<String> {
+        self.repos
+            .iter()
+            .filter(|(_, repo)| path.starts_with(repo))
+            .max_by_key(|(_, repo)| repo.components().count())
+            .map(|(alias, _)| alias.clone())
+    }
+
     /// Get all sessions
     pub fn get_sessions(&self) -> Vec<Session> {
         self.sessions.values().cloned().collect()
@@ -37,6 +56,21 @@ impl SessionManager {
         self.sessions.get_mut(id)
     }
 
+    /// Create a session for Claude Code we are about to launch; it is linked
+    /// to Claude's session ID when its SessionStart hook arrives
+    pub fn create_session(
+        &mut self,
+        repo_path: PathBuf,
+        repo_alias: Option<String>,
+        prompt: String,
+    ) -> Session {
+        let mut session = Session::new(repo_path, repo_alias, prompt);
+        session.refresh_git_branch();
+        session.record_event("Launching Claude Code");
+        self.sessions.insert(session.id, session.clone());
+        session
+    }
+
     /// Handle a hook event from Claude Code
     pub fn handle_hook_event(&mut self, event: HookEvent) -> Option<(Session, bool)> {
         match event {
@@ -44,29 +78,59 @@ impl SessionManager {
                 session_id,
                 transcript_path,
                 cwd,
+                launch_id,
+                pid,
+                pid_started,
             } => {
                 let cwd_path = PathBuf::from(&cwd);
 
+                // A session we launched reports in for the first time
+                let launched = launch_id
+                    .and_then(|id| self.sessions.get_mut(&id))
+                    .filter(|s| s.claude_session_id.is_none());
+                if let Some(session) = launched {
+                    session.claude_session_id = Some(session_id.clone());
+                    session.transcript_path = transcript_path.map(PathBuf::from);
+                    session.claude_pid = pid;
+                    session.claude_started = pid_started;
+                    session.status = SessionStatus::Running;
+                    session.refresh_git_branch();
+                    session.record_event("Session started");
+                    self.claude_id_map.insert(session_id, session.id);
+                    return Some((session.clone(), true));
+                }
+
                 // Check if we already have this session by Claude ID
                 if let Some(&our_id) = self.claude_id_map.get(&session_id) {
                     // Session already exists, just update it
                     if let Some(session) = self.sessions.get_mut(&our_id) {
                         let old_status = session.status.clone();
                         session.status = SessionStatus::Running;
+                        if pid.is_some() {
+                            session.claude_pid = pid;
+                            session.claude_started = pid_started;
+                        }
+                        session.refresh_git_branch();
+                        session.record_event("Session resumed");
                         let changed = old_status != session.status;
                         return Some((session.clone(), changed));
                     }
                 }
 
                 // Create a new session for this external Claude Code instance
-                let session = Session::new(cwd_path, None, "External session".to_string());
+                let alias = self.alias_for(&cwd_path);
+                let session = Session::new(cwd_path, alias, "External session".to_string());
                 let id = session.id;
 
                 // Update session with Claude's info
                 let mut session = session;
                 session.claude_session_id = Some(session_id.clone());
                 session.transcript_path = transcript_path.map(PathBuf::from);
+                session.claude_pid = pid;
+                session.claude_started = pid_started;
                 session.status = SessionStatus::Running;
+                session.refresh_git_branch();
+                session.record_event("Session started");
 
                 self.sessions.insert(id, session.clone());
                 self.claude_id_map.insert(session_id, id);
@@ -81,6 +145,7 @@ impl SessionManager {
                         let old_status = session.status.clone();
                         session.status = SessionStatus::Completed;
                         session.ended_at = Some(Utc::now());
+                        session.record_event("Session ended");
                         let changed = old_status != session.status;
                         return Some((session.clone(), changed));
                     }
@@ -111,6 +176,7 @@ impl SessionManager {
                         };
 
                         session.status = SessionStatus::WaitingForInput(wait_reason);
+                        session.record_event(message);
                         let changed = old_status != session.status;
                         return Some((session.clone(), changed));
                     }
@@ -118,12 +184,43 @@ impl SessionManager {
                 None
             }
 
-            HookEvent::Stop { session_id } => {
+            HookEvent::PermissionRequest {
+                session_id,
+                tool_name,
+                ..
+            } => {
+                if let Some(&our_id) = self.claude_id_map.get(&session_id) {
+                    if let Some(session) = self.sessions.get_mut(&our_id) {
+                        let old_status = session.status.clone();
+                        session.status = SessionStatus::WaitingForInput(WaitReason::PermissionPrompt);
+                        session.record_event(format!("Permission requested for {}", tool_name));
+                        let changed = old_status != session.status;
+                        return Some((session.clone(), changed));
+                    }
+                }
+                None
+            }
+
+            HookEvent::Stop { session_id, .. } => {
                 if let Some(&our_id) = self.claude_id_map.get(&session_id) {
                     if let Some(session) = self.sessions.get_mut(&our_id) {
                         let old_status = session.status.clone();
                         // Claude finished responding - set to waiting for input
                         session.status = SessionStatus::WaitingForInput(WaitReason::Stopped);
+                        session.record_event("Claude finished responding");
+                        let changed = old_status != session.status;
+                        return Some((session.clone(), changed));
+                    }
+                }
+                None
+            }
+
+            HookEvent::UserPromptSubmit { session_id } => {
+                if let Some(&our_id) = self.claude_id_map.get(&session_id) {
+                    if let Some(session) = self.sessions.get_mut(&our_id) {
+                        let old_status = session.status.clone();
+                        session.status = SessionStatus::Running;
+                        session.record_event("Prompt submitted");
                         let changed = old_status != session.status;
                         return Some((session.clone(), changed));
                     }
@@ -133,6 +230,85 @@ impl SessionManager {
         }
     }
 
+    /// Transcript path of the session Claude knows by this ID
+    pub fn transcript_path(&self, claude_session_id: &str) -> Option<PathBuf> {
+        let id = self.claude_id_map.get(claude_session_id)?;
+        self.sessions.get(id)?.transcript_path.clone()
+    }
+
+    /// Store Claude's latest reply, read from the transcript by the caller
+    pub fn set_last_message(&mut self, claude_session_id: &str, message: Option<String>) {
+        let session = self
+            .claude_id_map
+            .get(claude_session_id)
+            .and_then(|id| self.sessions.get_mut(id));
+        if let Some(session) = session {
+            session.last_message = message;
+        }
+    }
+
+    /// Find the session whose Slack thread starts at the given message
+    pub fn find_by_thread(&self, channel_id: &str, thread_ts: &str) -> Option<&Session> {
+        self.sessions.values().find(|s| {
+            s.slack_thread
+                .as_ref()
+                .is_some_and(|t| t.channel_id == channel_id && t.parent_ts == thread_ts)
+        })
+    }
+
+    /// Find sessions whose ID starts with the given prefix
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<&Session> {
+        self.sessions
+            .values()
+            .filter(|s| s.id.to_string().starts_with(prefix))
+            .collect()
+    }
+
+    /// Set a session's status and record why, returning the updated session
+    /// and whether its status changed
+    pub fn set_status(
+        &mut self,
+        session_id: &Uuid,
+        status: SessionStatus,
+        event: &str,
+    ) -> Option<(Session, bool)> {
+        let session = self.sessions.get_mut(session_id)?;
+        let changed = session.status != status;
+        session.status = status;
+        session.record_event(event);
+        Some((session.clone(), changed))
+    }
+
+    /// Record how a session's Claude Code process exited, unless its hooks
+    /// already ended the session. Returns the session when that changed it.
+    pub fn process_exited(&mut self, session_id: &Uuid, result: Result<(), String>) -> Option<Session> {
+        let session = self.sessions.get_mut(session_id).filter(|s| s.is_active())?;
+        match result {
+            Ok(()) => {
+                session.status = SessionStatus::Completed;
+                session.record_event("Claude Code exited");
+            }
+            Err(reason) => {
+                session.record_event(format!("Claude Code failed: {}", reason));
+                session.status = SessionStatus::Failed(reason);
+            }
+        }
+        session.ended_at = Some(Utc::now());
+        // The PID may be handed to another process from now on
+        session.claude_pid = None;
+        session.claude_started = None;
+        Some(session.clone())
+    }
+
+    /// Forget a session, e.g. one whose launch failed
+    pub fn remove_session(&mut self, session_id: &Uuid) {
+        if let Some(session) = self.sessions.remove(session_id) {
+            if let Some(claude_id) = &session.claude_session_id {
+                self.claude_id_map.remove(claude_id);
+            }
+        }
+    }
+
     /// Set the Slack thread for a session
     pub fn set_slack_thread(&mut self, session_id: Uuid, thread: SlackThread) {
         if let Some(session) = self.sessions.get_mut(&session_id) {
@@ -153,11 +329,7 @@ impl SessionManager {
             .collect();
 
         for id in to_remove {
-            if let Some(session) = self.sessions.remove(&id) {
-                if let Some(claude_id) = &session.claude_session_id {
-                    self.claude_id_map.remove(claude_id);
-                }
-            }
+            self.remove_session(&id);
         }
     }
 }
@@ -167,3 +339,82 @@ impl Default for SessionManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launched_session_links_on_start() {
+        let mut manager = SessionManager::new();
+        let session = manager.create_session(
+            PathBuf::from("/tmp/repo"),
+            Some("repo".to_string()),
+            "Fix the build".to_string(),
+        );
+        assert_eq!(session.status, SessionStatus::Starting);
+
+        let start = |launch_id| HookEvent::SessionStart {
+            session_id: "claude-1".to_string(),
+            transcript_path: None,
+            cwd: "/tmp/repo".to_string(),
+            launch_id,
+            pid: Some(4242),
+            pid_started: Some("Tue Mar  3 09:00:00 2026".to_string()),
+        };
+        let (linked, changed) = manager.handle_hook_event(start(Some(session.id))).unwrap();
+        assert!(changed);
+        assert_eq!(linked.id, session.id);
+        assert_eq!(linked.prompt, "Fix the build");
+        assert_eq!(linked.claude_session_id.as_deref(), Some("claude-1"));
+        assert_eq!(linked.status, SessionStatus::Running);
+        assert_eq!(linked.claude_pid, Some(4242));
+        assert!(linked.claude_started.is_some());
+
+        // Later hooks find it by Claude's ID, and a resume doesn't duplicate it
+        manager.handle_hook_event(start(None)).unwrap();
+        assert_eq!(manager.get_sessions().len(), 1);
+
+        // A new prompt after Claude stopped sets it running again
+        manager.handle_hook_event(HookEvent::Stop {
+            session_id: "claude-1".to_string(),
+            reply_timeout: None,
+        });
+        let prompt = HookEvent::UserPromptSubmit {
+            session_id: "claude-1".to_string(),
+        };
+        let (_, changed) = manager.handle_hook_event(prompt).unwrap();
+        assert!(changed);
+
+        // The exit only counts if the hooks didn't end the session first
+        manager.handle_hook_event(HookEvent::SessionEnd {
+            session_id: "claude-1".to_string(),
+        });
+        assert!(manager.process_exited(&session.id, Err("killed".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_alias_from_cwd() {
+        let mut manager = SessionManager::with_repos(vec![
+            ("work".to_string(), PathBuf::from("/home/me/work")),
+            ("api".to_string(), PathBuf::from("/home/me/work/api")),
+        ]);
+        let mut start = |session_id: &str, cwd: &str| {
+            let event = HookEvent::SessionStart {
+                session_id: session_id.to_string(),
+                transcript_path: None,
+                cwd: cwd.to_string(),
+                launch_id: None,
+                pid: None,
+                pid_started: None,
+            };
+            manager.handle_hook_event(event).unwrap().0.repo_alias
+        };
+
+        assert_eq!(start("1", "/home/me/work/api/src").as_deref(), Some("api"));
+        assert_eq!(start("2", "/home/me/work/web").as_deref(), Some("work"));
+        // Only whole path components match
+        assert_eq!(start("3", "/home/me/work/api-v2").as_deref(), Some("work"));
+        assert_eq!(start("4", "/tmp/scratch"), None);
+    }
+}
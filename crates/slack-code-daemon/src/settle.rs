@@ -0,0 +1,143 @@
+use slack_code_common::session::{Session, SessionStatus};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::outbox::OutboxMessage;
+
+/// Thread replies held back until a session has been idle for a while
+struct Held {
+    deadline: Instant,
+    messages: Vec<OutboxMessage>,
+}
+
+/// Holds waiting-for-input replies for a settle window, so a quick
+/// Stop → prompt → Stop round trip doesn't ping the user every time
+pub struct SettleWindow {
+    window: Duration,
+    held: HashMap<Uuid, Held>,
+}
+
+impl SettleWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Messages to send now for a session update; the rest are held.
+    /// `urgent` updates (someone is waiting on Slack) are never held.
+    pub fn filter(
+        &mut self,
+        session: &Session,
+        messages: Vec<OutboxMessage>,
+        urgent: bool,
+        now: Instant,
+    ) -> Vec<OutboxMessage> {
+        if self.window.is_zero() {
+            return messages;
+        }
+
+        match session.status {
+            SessionStatus::Starting | SessionStatus::Running => {
+                // Back at work before the window ran out
+                if self.held.remove(&session.id).is_some() {
+                    tracing::debug!("Dropped held notification for session {}", session.id);
+                }
+                messages
+            }
+            SessionStatus::WaitingForInput(_) if urgent => {
+                // This update supersedes whatever was held
+                self.held.remove(&session.id);
+                messages
+            }
+            SessionStatus::WaitingForInput(_) => {
+                let (replies, rest): (Vec<_>, Vec<_>) = messages
+                    .into_iter()
+                    .partition(|m| matches!(m, OutboxMessage::Reply { .. }));
+                if !replies.is_empty() {
+                    // The latest reply replaces an earlier one still held
+                    self.held.insert(
+                        session.id,
+                        Held {
+                            deadline: now + self.window,
+                            messages: replies,
+                        },
+                    );
+                }
+                rest
+            }
+            SessionStatus::Completed | SessionStatus::Failed(_) => {
+                // The session is over, so the held reply was its last word
+                let mut out = self
+                    .held
+                    .remove(&session.id)
+                    .map(|held| held.messages)
+                    .unwrap_or_default();
+                out.extend(messages);
+                out
+            }
+        }
+    }
+
+    /// Take the held messages whose window has run out
+    pub fn due(&mut self, now: Instant) -> Vec<(Uuid, Vec<OutboxMessage>)> {
+        let due: Vec<Uuid> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        due.into_iter()
+            .filter_map(|id| self.held.remove(&id).map(|held| (id, held.messages)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slack_code_common::session::WaitReason;
+    use std::path::PathBuf;
+
+    fn messages() -> Vec<OutboxMessage> {
+        vec![
+            OutboxMessage::SetReaction {
+                name: "raised_hand".to_string(),
+            },
+            OutboxMessage::reply_text("Claude stopped"),
+        ]
+    }
+
+    #[test]
+    fn test_settle_window() {
+        let mut settle = SettleWindow::new(Duration::from_secs(5));
+        let mut session = Session::new(PathBuf::from("/tmp/repo"), None, "test".to_string());
+        let start = Instant::now();
+
+        // The reply is held, the reaction goes out right away
+        session.status = SessionStatus::WaitingForInput(WaitReason::Stopped);
+        let now = settle.filter(&session, messages(), false, start);
+        assert!(matches!(now[..], [OutboxMessage::SetReaction { .. }]));
+        assert!(settle.due(start + Duration::from_secs(4)).is_empty());
+
+        // Working again within the window drops it
+        session.status = SessionStatus::Running;
+        assert_eq!(settle.filter(&session, Vec::new(), false, start).len(), 0);
+        assert!(settle.due(start + Duration::from_secs(6)).is_empty());
+
+        // Idle for the whole window sends it
+        session.status = SessionStatus::WaitingForInput(WaitReason::Stopped);
+        settle.filter(&session, messages(), false, start);
+        let due = settle.due(start + Duration::from_secs(5));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, session.id);
+        assert!(matches!(due[0].1[..], [OutboxMessage::Reply { .. }]));
+
+        // Urgent updates and a zero window never hold anything
+        assert_eq!(settle.filter(&session, messages(), true, start).len(), 2);
+        let mut off = SettleWindow::new(Duration::ZERO);
+        assert_eq!(off.filter(&session, messages(), false, start).len(), 2);
+    }
+}
@@ -1,35 +1,121 @@
+use crate::blocks;
+use crate::outbox::OutboxMessage;
+use crate::transcript;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use slack_code_common::config::{
+    route_channel, NotificationsConfig, QuietMode, RouteConfig, TemplatesConfig,
+    TranscriptConfig,
+};
 use slack_code_common::session::{Session, SessionStatus, SlackThread};
+use slack_code_common::Config;
+use slack_morphism::errors::SlackClientError;
 use slack_morphism::prelude::*;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Longest tool input shown in a permission prompt
+const MAX_TOOL_INPUT_CHARS: usize = 2500;
+
+/// A posted message that can be updated later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlackMessageRef {
+    pub channel_id: String,
+    pub ts: String,
+}
 
 /// Slack service for Socket Mode and Web API
 pub struct SlackService {
     client: Arc<SlackHyperClient>,
     bot_token: SlackApiToken,
     /// DM channel ID for the bot's user (cached after first use)
-    dm_channel_id: Option<String>,
+    dm_channel_id: Mutex<Option<String>>,
     /// User's Slack Member ID for opening DM channel
     user_id: String,
+    /// How status changes are posted
+    notifications: NotificationsConfig,
+    /// Name of the machine the sessions run on
+    host: String,
+    /// Channels for specific repositories
+    routes: Vec<RouteConfig>,
+    /// Whether and how transcripts are uploaded when a session ends
+    transcript: TranscriptConfig,
+    /// Custom wording for root messages and status replies
+    templates: TemplatesConfig,
+    /// Status reaction currently on each root message, by message ts. Root
+    /// messages posted since startup start out with `None`; ones missing
+    /// were posted by an earlier run.
+    status_reactions: Mutex<HashMap<String, Option<String>>>,
+    /// Thread links already looked up, by root message ts
+    permalinks: Mutex<HashMap<String, String>>,
+    /// Status changes kept out of Slack during quiet hours
+    quiet_log: Mutex<Vec<QuietEvent>>,
+}
+
+/// A status change that happened during quiet hours
+#[derive(Debug, Clone)]
+pub struct QuietEvent {
+    pub at: DateTime<Utc>,
+    pub repo: String,
+    pub status: SessionStatus,
+}
+
+impl QuietEvent {
+    fn new(session: &Session) -> Self {
+        Self {
+            at: Utc::now(),
+            repo: session.display_name(),
+            status: session.status.clone(),
+        }
+    }
+}
+
+/// Create a Slack Web API client, talking to `api_url` instead of slack.com when set
+pub fn new_client(api_url: Option<&str>) -> Result<Arc<SlackHyperClient>> {
+    let connector = match api_url {
+        Some(url) => {
+            // Stand-ins usually listen on plain HTTP, which the default connector refuses
+            let https = hyper_rustls::HttpsConnectorBuilder::new()
+                .with_native_roots()?
+                .https_or_http()
+                .enable_http1()
+                .enable_http2()
+                .build();
+            SlackClientHyperConnector::from(https).with_slack_api_url(url.trim_end_matches('/'))
+        }
+        None => SlackClientHyperConnector::new()?,
+    };
+    Ok(Arc::new(SlackClient::new(connector)))
 }
 
 impl SlackService {
-    pub fn new(bot_token: &str, user_id: String) -> Result<Self> {
-        let client = Arc::new(SlackClient::new(SlackClientHyperConnector::new()?));
+    pub fn new(config: &Config) -> Result<Self> {
+        let client = new_client(config.slack.api_url.as_deref())?;
 
         Ok(Self {
             client,
-            bot_token: SlackApiToken::new(bot_token.into()),
-            dm_channel_id: None,
-            user_id,
+            bot_token: SlackApiToken::new(config.slack.get_bot_token().into()),
+            dm_channel_id: Mutex::new(None),
+            user_id: config.slack.user_id.clone(),
+            notifications: config.notifications.clone(),
+            host: nix::unistd::gethostname()
+                .map(|h| h.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| "unknown".to_string()),
+            routes: config.routes.clone(),
+            transcript: config.transcript.clone(),
+            templates: config.templates.clone(),
+            status_reactions: Mutex::new(HashMap::new()),
+            permalinks: Mutex::new(HashMap::new()),
+            quiet_log: Mutex::new(Vec::new()),
         })
     }
 
     /// Ensure DM channel is available, opening one via conversations.open if necessary
-    async fn ensure_dm_channel(&mut self) -> Result<String> {
+    async fn ensure_dm_channel(&self) -> Result<String> {
         // Return cached channel if available
-        if let Some(ref channel) = self.dm_channel_id {
-            return Ok(channel.clone());
+        if let Some(channel) = self.dm_channel_id.lock().unwrap().clone() {
+            return Ok(channel);
         }
 
         // Open DM channel using user_id via conversations.open
@@ -42,92 +128,468 @@ impl SlackService {
             Ok(response) => {
                 let channel_id = response.channel.id.to_string();
                 tracing::info!("Opened DM channel {} for user {}", channel_id, self.user_id);
-                self.dm_channel_id = Some(channel_id.clone());
+                *self.dm_channel_id.lock().unwrap() = Some(channel_id.clone());
                 Ok(channel_id)
             }
             Err(e) => {
                 tracing::error!("Failed to open DM channel for user {}: {}", self.user_id, e);
-                Err(anyhow::anyhow!(
-                    "Failed to open DM channel with user {}: {}",
-                    self.user_id,
-                    e
-                ))
+                // Keep the Slack error inspectable so the outbox can decide to retry
+                let message = format!("Failed to open DM channel with user {}", self.user_id);
+                Err(anyhow::Error::new(e).context(message))
+            }
+        }
+    }
+
+    /// Channel a session is routed to, if it is not posted to your DMs
+    fn routed_channel(&self, session: &Session) -> Option<&str> {
+        route_channel(
+            &self.routes,
+            &session.repo_path,
+            session.repo_alias.as_deref(),
+        )
+    }
+
+    /// Root message for a session; in a shared channel it mentions the owner
+    fn session_root(&self, session: &Session) -> SlackMessageContent {
+        let mention = self
+            .routed_channel(session)
+            .filter(|_| self.quiet_mode(Utc::now()).is_none())
+            .map(|_| self.user_id.as_str());
+        blocks::session_root(
+            session,
+            &self.host,
+            &self.templates,
+            mention,
+            self.notifications.update_root,
+        )
+    }
+
+    /// Root message that starts a session's thread, and its status reaction
+    pub fn start_messages(&self, session: &Session) -> Vec<OutboxMessage> {
+        let mut messages = vec![OutboxMessage::StartThread {
+            channel: self.routed_channel(session).map(str::to_string),
+            content: self.session_root(session),
+        }];
+        messages.extend(self.status_reaction(session));
+        messages
+    }
+
+    fn status_reaction(&self, session: &Session) -> Option<OutboxMessage> {
+        self.notifications
+            .status_reactions
+            .then(|| OutboxMessage::SetReaction {
+                name: blocks::status_reaction(&session.status).to_string(),
+            })
+    }
+
+    /// Messages for a status change: a refreshed root message and/or a thread reply.
+    /// With `status_changed` unset only the root message is refreshed.
+    pub fn status_messages(
+        &self,
+        session: &Session,
+        status_changed: bool,
+        awaiting_reply: bool,
+    ) -> Vec<OutboxMessage> {
+        let mut messages = Vec::new();
+
+        if self.notifications.update_root {
+            messages.push(OutboxMessage::UpdateRoot {
+                content: self.session_root(session),
+            });
+        }
+
+        // Sent on every update; delivery skips it when the reaction is unchanged
+        messages.extend(self.status_reaction(session));
+
+        if status_changed && self.notifications.wants_thread_reply(&session.status) {
+            let message =
+                blocks::status_text(session, &self.templates, &self.host, awaiting_reply);
+            match self.quiet_mode(Utc::now()) {
+                None => messages.push(OutboxMessage::Reply {
+                    content: blocks::status_reply(session, message, Some(&self.user_id)),
+                }),
+                Some(mode) => {
+                    // Summed up once quiet hours are over
+                    self.quiet_log.lock().unwrap().push(QuietEvent::new(session));
+                    if mode == QuietMode::Silent {
+                        messages.push(OutboxMessage::Reply {
+                            content: blocks::status_reply(session, message, None),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Archive what the agent did once the session is over
+        if status_changed && session.status == SessionStatus::Completed && self.transcript.upload {
+            match transcript::render_markdown(session, self.transcript.max_kb * 1024) {
+                Some(content) => messages.push(OutboxMessage::UploadFile {
+                    filename: format!("transcript-{}.md", blocks::short_id(session)),
+                    title: format!("Transcript: {}", session.display_name()),
+                    content,
+                }),
+                None => tracing::warn!("No readable transcript for session {}", session.id),
             }
         }
+
+        messages
+    }
+
+    /// Quiet hours mode if quiet hours are on at `now`
+    fn quiet_mode(&self, now: DateTime<Utc>) -> Option<QuietMode> {
+        let quiet_hours = self.notifications.quiet_hours.as_ref()?;
+        quiet_hours.quiet_until(now).map(|_| quiet_hours.mode)
+    }
+
+    /// When a message that would notify you may go out, if quiet hours hold it back
+    pub fn held_until(&self, message: &OutboxMessage, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let quiet_hours = self.notifications.quiet_hours.as_ref()?;
+        if quiet_hours.mode != QuietMode::Hold {
+            return None;
+        }
+        match message {
+            OutboxMessage::StartThread { .. }
+            | OutboxMessage::Reply { .. }
+            | OutboxMessage::UploadFile { .. }
+            | OutboxMessage::Direct { .. } => quiet_hours.quiet_until(now),
+            OutboxMessage::UpdateRoot { .. } | OutboxMessage::SetReaction { .. } => None,
+        }
+    }
+
+    /// Summary of what happened during quiet hours, once they are over
+    pub fn catch_up(&self, now: DateTime<Utc>) -> Option<OutboxMessage> {
+        if self.quiet_mode(now).is_some() {
+            return None;
+        }
+        let events = std::mem::take(&mut *self.quiet_log.lock().unwrap());
+        if events.is_empty() {
+            return None;
+        }
+        Some(OutboxMessage::Direct {
+            content: blocks::catch_up_summary(&self.user_id, &events),
+        })
     }
 
-    /// Post a message when a session starts
-    pub async fn post_session_start(&mut self, session: &Session) -> Result<SlackThread> {
-        let repo_name = session.display_name();
+    /// Send a queued message. Returns the thread when a root message was posted.
+    pub async fn deliver(
+        &self,
+        message: &OutboxMessage,
+        thread: Option<&SlackThread>,
+    ) -> Result<Option<SlackThread>> {
+        match message {
+            OutboxMessage::StartThread { channel, content } => {
+                // Post to the routed channel, or the DM (opened via conversations.open if needed)
+                let channel = match channel {
+                    Some(channel) => channel.clone(),
+                    None => self.ensure_dm_channel().await?,
+                };
+
+                let session_api = self.client.open_session(&self.bot_token);
+                let response = session_api
+                    .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                        SlackChannelId::new(channel),
+                        content.clone(),
+                    ))
+                    .await?;
+
+                self.status_reactions
+                    .lock()
+                    .unwrap()
+                    .insert(response.ts.to_string(), None);
+                Ok(Some(SlackThread {
+                    channel_id: response.channel.to_string(),
+                    parent_ts: response.ts.to_string(),
+                }))
+            }
+            OutboxMessage::UpdateRoot { content } => {
+                let thread = thread.ok_or_else(|| anyhow::anyhow!("Session has no thread"))?;
+                let session_api = self.client.open_session(&self.bot_token);
+
+                session_api
+                    .chat_update(&SlackApiChatUpdateRequest::new(
+                        SlackChannelId::new(thread.channel_id.clone()),
+                        content.clone(),
+                        SlackTs::new(thread.parent_ts.clone()),
+                    ))
+                    .await?;
 
-        // Format the message with Block Kit
-        let text = format!(
-            "*New Claude Code Session*\n\
-            *Repository:* `{}`\n\
-            *Prompt:* {}",
-            repo_name, session.prompt
-        );
+                Ok(None)
+            }
+            OutboxMessage::Reply { content } => {
+                let thread = thread.ok_or_else(|| anyhow::anyhow!("Session has no thread"))?;
+                self.post_thread_content(thread, content.clone()).await?;
+                Ok(None)
+            }
+            OutboxMessage::Direct { content } => {
+                let channel = self.ensure_dm_channel().await?;
+                let session_api = self.client.open_session(&self.bot_token);
+                session_api
+                    .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                        SlackChannelId::new(channel),
+                        content.clone(),
+                    ))
+                    .await?;
+                Ok(None)
+            }
+            OutboxMessage::SetReaction { name } => {
+                let thread = thread.ok_or_else(|| anyhow::anyhow!("Session has no thread"))?;
+                self.set_status_reaction(thread, name).await?;
+                Ok(None)
+            }
+            OutboxMessage::UploadFile {
+                filename,
+                title,
+                content,
+            } => {
+                let thread = thread.ok_or_else(|| anyhow::anyhow!("Session has no thread"))?;
+                self.upload_thread_file(thread, filename, title, content)
+                    .await?;
+                Ok(None)
+            }
+        }
+    }
 
-        // Ensure DM channel is available (opens via conversations.open if needed)
-        let channel = self.ensure_dm_channel().await?;
+    /// Swap the status reaction on a root message for `name`. Safe to retry: a
+    /// reaction that is already gone or already there counts as done.
+    async fn set_status_reaction(&self, thread: &SlackThread, name: &str) -> Result<()> {
+        let previous = self
+            .status_reactions
+            .lock()
+            .unwrap()
+            .get(&thread.parent_ts)
+            .cloned();
+        let stale = match previous {
+            Some(Some(ref previous)) if previous == name => return Ok(()),
+            Some(previous) => previous.into_iter().collect(),
+            // Posted before a restart, so any status reaction may be there
+            None => blocks::STATUS_REACTIONS
+                .iter()
+                .filter(|reaction| **reaction != name)
+                .map(|reaction| reaction.to_string())
+                .collect::<Vec<_>>(),
+        };
 
         let session_api = self.client.open_session(&self.bot_token);
-        let response = session_api
-            .chat_post_message(&SlackApiChatPostMessageRequest::new(
-                SlackChannelId::new(channel),
-                SlackMessageContent::new().with_text(text),
+        let channel = SlackChannelId::new(thread.channel_id.clone());
+        let ts = SlackTs::new(thread.parent_ts.clone());
+
+        for previous in stale {
+            let result = session_api
+                .reactions_remove(
+                    &SlackApiReactionsRemoveRequest::new(SlackReactionName::new(previous))
+                        .with_channel(channel.clone())
+                        .with_timestamp(ts.clone()),
+                )
+                .await;
+            ignore_api_error(result.map(|_| ()), "no_reaction")?;
+        }
+
+        let result = session_api
+            .reactions_add(&SlackApiReactionsAddRequest::new(
+                channel,
+                SlackReactionName::new(name.to_string()),
+                ts,
             ))
-            .await?;
+            .await;
+        ignore_api_error(result.map(|_| ()), "already_reacted")?;
 
-        Ok(SlackThread {
-            channel_id: response.channel.to_string(),
-            parent_ts: response.ts.to_string(),
-        })
+        self.status_reactions
+            .lock()
+            .unwrap()
+            .insert(thread.parent_ts.clone(), Some(name.to_string()));
+        Ok(())
     }
 
-    /// Post a thread reply with status update
-    pub async fn post_thread_reply(&self, thread: &SlackThread, session: &Session) -> Result<()> {
+    /// Upload a file into a session thread (files.getUploadURLExternal, then
+    /// files.completeUploadExternal to share it)
+    async fn upload_thread_file(
+        &self,
+        thread: &SlackThread,
+        filename: &str,
+        title: &str,
+        content: &str,
+    ) -> Result<()> {
         let session_api = self.client.open_session(&self.bot_token);
 
-        let message = format!("<@{}> {}", self.user_id, format_status_message(session));
+        let upload = session_api
+            .get_upload_url_external(&SlackApiFilesGetUploadUrlExternalRequest::new(
+                filename.to_string(),
+                content.len(),
+            ))
+            .await?;
+
+        session_api
+            .files_upload_via_url(&SlackApiFilesUploadViaUrlRequest::new(
+                upload.upload_url,
+                content.as_bytes().to_vec(),
+                "text/markdown".to_string(),
+            ))
+            .await?;
 
         session_api
+            .files_complete_upload_external(
+                &SlackApiFilesCompleteUploadExternalRequest::new(vec![
+                    SlackApiFilesComplete::new(upload.file_id).with_title(title.to_string()),
+                ])
+                .with_channel_id(SlackChannelId::new(thread.channel_id.clone()))
+                .with_thread_ts(SlackTs::new(thread.parent_ts.clone())),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Post a reply in a session thread
+    async fn post_thread_content(
+        &self,
+        thread: &SlackThread,
+        content: SlackMessageContent,
+    ) -> Result<SlackMessageRef> {
+        let session_api = self.client.open_session(&self.bot_token);
+
+        let response = session_api
             .chat_post_message(
                 &SlackApiChatPostMessageRequest::new(
                     SlackChannelId::new(thread.channel_id.clone()),
-                    SlackMessageContent::new().with_text(message),
+                    content,
                 )
                 .with_thread_ts(SlackTs::new(thread.parent_ts.clone())),
             )
             .await?;
 
-        Ok(())
+        Ok(SlackMessageRef {
+            channel_id: response.channel.to_string(),
+            ts: response.ts.to_string(),
+        })
     }
-}
 
-/// Format a status message for Slack
-fn format_status_message(session: &Session) -> String {
-    match &session.status {
-        SessionStatus::Starting => "Starting Claude Code session...".to_string(),
-        SessionStatus::Running => "Claude is working on your request...".to_string(),
-        SessionStatus::WaitingForInput(reason) => {
-            match reason {
-                slack_code_common::session::WaitReason::PermissionPrompt => {
-                    "⏸️ Waiting for permission approval in terminal".to_string()
-                }
-                slack_code_common::session::WaitReason::Stopped => {
-                    "✅ Claude finished working! Waiting for your next input in terminal"
-                        .to_string()
-                }
-                slack_code_common::session::WaitReason::PlanApproval => {
-                    "📋 Waiting for plan approval in terminal".to_string()
+    /// Post Approve/Deny buttons for a permission request in the session thread
+    pub async fn post_permission_request(
+        &self,
+        thread: &SlackThread,
+        request_id: Uuid,
+        summary: &str,
+    ) -> Result<SlackMessageRef> {
+        self.post_thread_content(
+            thread,
+            blocks::permission_request(&self.user_id, request_id, summary),
+        )
+        .await
+    }
+
+    /// Link to a session thread's root message
+    pub async fn thread_permalink(&self, thread: &SlackThread) -> Result<String> {
+        if let Some(link) = self.permalinks.lock().unwrap().get(&thread.parent_ts) {
+            return Ok(link.clone());
+        }
+
+        let session_api = self.client.open_session(&self.bot_token);
+
+        let response = session_api
+            .chat_get_permalink(&SlackApiChatGetPermalinkRequest::new(
+                SlackChannelId::new(thread.channel_id.clone()),
+                SlackTs::new(thread.parent_ts.clone()),
+            ))
+            .await?;
+
+        let link = response.permalink.to_string();
+        self.permalinks
+            .lock()
+            .unwrap()
+            .insert(thread.parent_ts.clone(), link.clone());
+        Ok(link)
+    }
+
+    /// Publish the owner's App Home tab with the given sessions
+    pub async fn publish_home(&self, sessions: &[Session]) -> Result<()> {
+        let mut permalinks = HashMap::new();
+        for session in sessions {
+            if let Some(ref thread) = session.slack_thread {
+                match self.thread_permalink(thread).await {
+                    Ok(link) => {
+                        permalinks.insert(session.id, link);
+                    }
+                    Err(e) => tracing::debug!("No permalink for session {}: {}", session.id, e),
                 }
             }
         }
-        SessionStatus::Failed(error) => {
-            format!("❌ Session failed: {}", error)
+
+        let session_api = self.client.open_session(&self.bot_token);
+        session_api
+            .views_publish(&SlackApiViewsPublishRequest::new(
+                SlackUserId::new(self.user_id.clone()),
+                blocks::app_home(sessions, &permalinks, chrono::Utc::now()),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Answer a slash command; responses are only visible to the person who ran it
+    pub async fn respond_to_command(
+        &self,
+        response_url: &SlackResponseUrl,
+        content: SlackMessageContent,
+    ) -> Result<()> {
+        self.client
+            .respond_to_event(response_url, &SlackApiPostWebhookMessageRequest::new(content))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Replace the buttons of a permission request with its outcome
+    pub async fn resolve_permission_request(
+        &self,
+        message: &SlackMessageRef,
+        summary: &str,
+        outcome: &str,
+    ) -> Result<()> {
+        let session_api = self.client.open_session(&self.bot_token);
+
+        session_api
+            .chat_update(&SlackApiChatUpdateRequest::new(
+                SlackChannelId::new(message.channel_id.clone()),
+                blocks::permission_resolved(summary, outcome),
+                SlackTs::new(message.ts.clone()),
+            ))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Describe a tool call for a permission prompt
+pub fn describe_tool_call(tool_name: &str, tool_input: &serde_json::Value) -> String {
+    let detail = match tool_name {
+        "Bash" => tool_input["command"].as_str(),
+        "Edit" | "MultiEdit" | "Write" | "Read" | "NotebookEdit" => {
+            tool_input["file_path"].as_str()
         }
-        // Completed status is filtered out in daemon.rs before calling this function,
-        SessionStatus::Completed => unreachable!("Completed status should not reach format_status_message"),
+        "WebFetch" => tool_input["url"].as_str(),
+        _ => None,
+    }
+    .map(str::to_string)
+    .unwrap_or_else(|| serde_json::to_string_pretty(tool_input).unwrap_or_default());
+
+    format!(
+        "Claude wants to use *{}*\n```{}```",
+        escape_mrkdwn(tool_name),
+        blocks::truncate(&escape_mrkdwn(&detail), MAX_TOOL_INPUT_CHARS)
+    )
+}
+
+/// Treat one Slack error code as success
+fn ignore_api_error(result: ClientResult<()>, code: &str) -> ClientResult<()> {
+    match result {
+        Err(SlackClientError::ApiError(ref e)) if e.code == code => Ok(()),
+        other => other,
     }
 }
+
+/// Escape the characters Slack treats as control sequences in mrkdwn
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
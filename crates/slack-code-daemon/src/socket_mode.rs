@@ -0,0 +1,286 @@
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use slack_code_common::ipc::DaemonStatus;
+use slack_morphism::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Delay before the first reconnect attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for the reconnect delay
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Inbound Slack events delivered over Socket Mode
+#[derive(Debug, Clone)]
+pub enum SocketModeEvent {
+    /// Events API callback (messages, app_home_opened, etc.)
+    EventsApi(Box<SlackPushEventCallback>),
+
+    /// Interactive payload (button clicks, etc.)
+    Interactive(Box<SlackInteractionEvent>),
+
+    /// Slash command invocation
+    SlashCommand(Box<SlackCommandEvent>),
+}
+
+/// Frames sent by Slack over the Socket Mode connection
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Frame {
+    Hello {},
+    Disconnect {
+        reason: String,
+    },
+    EventsApi {
+        envelope_id: String,
+        payload: Box<SlackPushEventCallback>,
+    },
+    Interactive {
+        envelope_id: String,
+        payload: Box<SlackInteractionEvent>,
+    },
+    SlashCommands {
+        envelope_id: String,
+        payload: Box<SlackCommandEvent>,
+    },
+}
+
+/// Just the envelope ID, for acknowledging frames we cannot parse
+#[derive(Debug, Deserialize)]
+struct EnvelopeProbe {
+    envelope_id: String,
+}
+
+/// Build the acknowledgement for an envelope
+fn ack_message(envelope_id: &str) -> Message {
+    Message::text(serde_json::json!({ "envelope_id": envelope_id }).to_string())
+}
+
+/// Where the WebSocket URL comes from
+enum Endpoint {
+    /// Ask Slack for a fresh URL via apps.connections.open on every connect
+    AppToken {
+        client: Arc<SlackHyperClient>,
+        token: SlackApiToken,
+    },
+
+    /// Always connect to the same URL (used for local stand-ins)
+    Fixed(String),
+}
+
+/// Keeps a Socket Mode WebSocket open, reconnecting with exponential backoff
+pub struct SocketModeClient {
+    endpoint: Endpoint,
+    /// Current connection state, observed by the daemon
+    status_tx: watch::Sender<DaemonStatus>,
+    /// Acknowledged envelopes are forwarded here
+    event_tx: mpsc::Sender<SocketModeEvent>,
+}
+
+impl SocketModeClient {
+    /// Create a client that authenticates with an app-level token (xapp-...)
+    pub fn new(
+        client: Arc<SlackHyperClient>,
+        app_token: &str,
+        event_tx: mpsc::Sender<SocketModeEvent>,
+        status_tx: watch::Sender<DaemonStatus>,
+    ) -> Self {
+        Self {
+            endpoint: Endpoint::AppToken {
+                client,
+                token: SlackApiToken::new(app_token.into()),
+            },
+            status_tx,
+            event_tx,
+        }
+    }
+
+    /// Create a client that connects to a fixed WebSocket URL
+    pub fn with_url(
+        url: impl Into<String>,
+        event_tx: mpsc::Sender<SocketModeEvent>,
+        status_tx: watch::Sender<DaemonStatus>,
+    ) -> Self {
+        Self {
+            endpoint: Endpoint::Fixed(url.into()),
+            status_tx,
+            event_tx,
+        }
+    }
+
+    /// Run the connection loop until the event receiver is dropped
+    pub async fn run(self) {
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            self.status_tx.send_replace(DaemonStatus::Connecting);
+
+            let reason = match self.connect_once(&mut backoff).await {
+                Ok(reason) => reason,
+                Err(e) => e.to_string(),
+            };
+
+            if self.event_tx.is_closed() {
+                break;
+            }
+
+            tracing::warn!(
+                "Socket Mode disconnected ({}), reconnecting in {:?}",
+                reason,
+                backoff
+            );
+            self.status_tx
+                .send_replace(DaemonStatus::Disconnected(reason));
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Resolve the WebSocket URL for the next connection attempt
+    async fn resolve_url(&self) -> Result<String> {
+        match &self.endpoint {
+            Endpoint::AppToken { client, token } => {
+                let session_api = client.open_session(token);
+                let response = session_api
+                    .apps_connections_open(&SlackApiAppsConnectionOpenRequest::new())
+                    .await?;
+                Ok(response.url.0.to_string())
+            }
+            Endpoint::Fixed(url) => Ok(url.clone()),
+        }
+    }
+
+    /// Hold a single connection open, returning the reason it ended
+    async fn connect_once(&self, backoff: &mut Duration) -> Result<String> {
+        let url = self.resolve_url().await?;
+        let (mut ws, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+
+        while let Some(frame) = ws.next().await {
+            let text = match frame? {
+                Message::Text(text) => text,
+                Message::Ping(data) => {
+                    ws.send(Message::Pong(data)).await?;
+                    continue;
+                }
+                Message::Close(frame) => {
+                    return Ok(frame
+                        .map(|f| f.reason.to_string())
+                        .filter(|r| !r.is_empty())
+                        .unwrap_or_else(|| "Connection closed".to_string()));
+                }
+                _ => continue,
+            };
+
+            let frame = match serde_json::from_str::<Frame>(text.as_str()) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    tracing::debug!("Unrecognized Socket Mode frame: {}", e);
+                    // Still acknowledge so Slack does not redeliver it
+                    if let Ok(probe) = serde_json::from_str::<EnvelopeProbe>(text.as_str()) {
+                        ws.send(ack_message(&probe.envelope_id)).await?;
+                    }
+                    continue;
+                }
+            };
+
+            let (envelope_id, event) = match frame {
+                Frame::Hello {} => {
+                    tracing::info!("Socket Mode connected");
+                    *backoff = INITIAL_BACKOFF;
+                    self.status_tx.send_replace(DaemonStatus::Connected);
+                    continue;
+                }
+                Frame::Disconnect { reason } => {
+                    // Slack asks us to reconnect (refresh_requested, warning, etc.)
+                    return Ok(reason);
+                }
+                Frame::EventsApi {
+                    envelope_id,
+                    payload,
+                } => (envelope_id, SocketModeEvent::EventsApi(payload)),
+                Frame::Interactive {
+                    envelope_id,
+                    payload,
+                } => (envelope_id, SocketModeEvent::Interactive(payload)),
+                Frame::SlashCommands {
+                    envelope_id,
+                    payload,
+                } => (envelope_id, SocketModeEvent::SlashCommand(payload)),
+            };
+
+            // Acknowledge before handing off; Slack expects this within 3 seconds
+            ws.send(ack_message(&envelope_id)).await?;
+
+            if self.event_tx.send(event).await.is_err() {
+                return Ok("Daemon shutting down".to_string());
+            }
+        }
+
+        Ok("Connection closed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const HELLO: &str = r#"{"type":"hello","num_connections":1,"connection_info":{"app_id":"A123"},"debug_info":{"host":"local"}}"#;
+
+    const SLASH_COMMAND: &str = r#"{"type":"slash_commands","envelope_id":"env-1","accepts_response_payload":true,"payload":{"team_id":"T1","channel_id":"D1","user_id":"U1","command":"/claude","text":"sessions","response_url":"https://hooks.slack.com/commands/1","trigger_id":"1.2"}}"#;
+
+    #[tokio::test]
+    async fn test_acknowledges_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let (event_tx, mut event_rx) = mpsc::channel(10);
+        let (status_tx, mut status_rx) =
+            watch::channel(DaemonStatus::Disconnected("not started".into()));
+        tokio::spawn(SocketModeClient::with_url(url, event_tx, status_tx).run());
+
+        // First connection: hello, one envelope, then Slack asks for a refresh
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::text(HELLO)).await.unwrap();
+        status_rx
+            .wait_for(|s| *s == DaemonStatus::Connected)
+            .await
+            .unwrap();
+
+        ws.send(Message::text(SLASH_COMMAND)).await.unwrap();
+        let ack = ws.next().await.unwrap().unwrap();
+        assert_eq!(ack.to_text().unwrap(), r#"{"envelope_id":"env-1"}"#);
+
+        match event_rx.recv().await.unwrap() {
+            SocketModeEvent::SlashCommand(cmd) => {
+                assert_eq!(cmd.text.as_deref(), Some("sessions"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        ws.send(Message::text(
+            r#"{"type":"disconnect","reason":"refresh_requested","debug_info":{"host":"local"}}"#,
+        ))
+        .await
+        .unwrap();
+        status_rx
+            .wait_for(|s| *s == DaemonStatus::Disconnected("refresh_requested".into()))
+            .await
+            .unwrap();
+
+        // The client comes back on its own
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::text(HELLO)).await.unwrap();
+        status_rx
+            .wait_for(|s| *s == DaemonStatus::Connected)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+//! Stops a session's Claude Code process: SIGINT first, then SIGTERM if it
+//! is still running after a grace period.
+
+use anyhow::Result;
+use nix::errno::Errno;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use slack_code_common::process;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How often to check whether the process has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What happened to the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    /// It had already exited
+    NotRunning,
+    /// It exited after SIGINT
+    Interrupted,
+    /// It only exited after SIGTERM
+    Terminated,
+    /// It ignored SIGTERM as well
+    StillRunning,
+}
+
+impl StopOutcome {
+    /// Whether the process is gone
+    pub fn exited(self) -> bool {
+        !matches!(self, StopOutcome::StillRunning)
+    }
+
+    /// Line logged in the session thread
+    pub fn describe(self) -> &'static str {
+        match self {
+            StopOutcome::NotRunning => "Claude Code had already exited",
+            StopOutcome::Interrupted => "⏹️ Claude Code stopped",
+            StopOutcome::Terminated => "⏹️ Claude Code ignored the interrupt and was terminated",
+            StopOutcome::StillRunning => {
+                "⚠️ Claude Code is still running after SIGTERM, stop it at its terminal"
+            }
+        }
+    }
+}
+
+/// Send SIGINT, and SIGTERM if the process hasn't exited within `grace`.
+/// Nothing is sent unless the process still has the recorded start time,
+/// since its PID may have been handed to another one.
+pub async fn stop_process(pid: u32, started: &str, grace: Duration) -> Result<StopOutcome> {
+    let current = tokio::task::spawn_blocking(move || process::start_time(pid)).await?;
+    if current.as_deref() != Some(started) {
+        return Ok(StopOutcome::NotRunning);
+    }
+    let pid = Pid::from_raw(pid as i32);
+
+    match kill(pid, Signal::SIGINT) {
+        Err(Errno::ESRCH) => return Ok(StopOutcome::NotRunning),
+        result => result?,
+    }
+    if wait_for_exit(pid, grace).await {
+        return Ok(StopOutcome::Interrupted);
+    }
+
+    tracing::info!(
+        "Process {} still running after SIGINT, sending SIGTERM",
+        pid
+    );
+    match kill(pid, Signal::SIGTERM) {
+        Err(Errno::ESRCH) => return Ok(StopOutcome::Interrupted),
+        result => result?,
+    }
+    if wait_for_exit(pid, grace).await {
+        Ok(StopOutcome::Terminated)
+    } else {
+        Ok(StopOutcome::StillRunning)
+    }
+}
+
+/// Whether the process exited within `timeout`
+async fn wait_for_exit(pid: Pid, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        // Signal 0 only checks that the process exists
+        if kill(pid, None) == Err(Errno::ESRCH) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::process::Command;
+
+    /// Run a shell script, reaping it as soon as it exits
+    async fn spawn(script: &str) -> u32 {
+        let mut child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        let pid = child.id().unwrap();
+        tokio::spawn(async move { child.wait().await });
+        // Give the script time to set up its trap
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        pid
+    }
+
+    #[tokio::test]
+    async fn test_stop_process() {
+        let grace = Duration::from_millis(500);
+
+        // Ignoring SIGINT (as background jobs may already) escalates to SIGTERM
+        let pid = spawn("trap '' INT; exec sleep 30").await;
+        let started = process::start_time(pid).unwrap();
+        assert_eq!(
+            stop_process(pid, "Thu Jan  1 00:00:00 1970", grace)
+                .await
+                .unwrap(),
+            StopOutcome::NotRunning
+        );
+        assert_eq!(
+            stop_process(pid, &started, grace).await.unwrap(),
+            StopOutcome::Terminated
+        );
+        assert_eq!(
+            stop_process(pid, &started, grace).await.unwrap(),
+            StopOutcome::NotRunning
+        );
+    }
+}
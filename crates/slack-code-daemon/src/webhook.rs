@@ -0,0 +1,224 @@
+//! Posts session events as JSON to user-configured URLs.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, KeyInit, Mac};
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use sha2::Sha256;
+use slack_code_common::config::{NotifyEvent, WebhookConfig};
+use slack_code_common::session::Session;
+use std::time::Duration;
+
+use crate::notify::{EventPayload, Notifier, SessionUpdate};
+
+/// Header holding `sha256=<hex HMAC of the body>` when a secret is set
+pub const SIGNATURE_HEADER: &str = "x-slack-code-signature";
+
+/// Header holding the event kind, e.g. `session_end`
+pub const EVENT_HEADER: &str = "x-slack-code-event";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before the first retry, doubled for each later one
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Sends every event it hears about to one URL
+pub struct WebhookNotifier {
+    url: http::Uri,
+    secret: Option<String>,
+    retries: u32,
+    client: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &WebhookConfig) -> Result<Self> {
+        let url = config
+            .url
+            .parse()
+            .with_context(|| format!("Invalid webhook URL {}", config.url))?;
+        let https = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()?
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .build();
+
+        Ok(Self {
+            url,
+            secret: config.secret.clone(),
+            retries: config.retries,
+            client: Client::builder(TokioExecutor::new()).build(https),
+        })
+    }
+
+    /// POST the event, retrying with backoff on network trouble, 5xx and 429
+    async fn post(&self, event: NotifyEvent, update: &SessionUpdate) -> Result<()> {
+        let body = serde_json::to_vec(&EventPayload::new(event, update))?;
+        let signature = self.secret.as_deref().map(|secret| sign(secret, &body));
+
+        let mut attempts = 0;
+        loop {
+            let error = match self.send(event, &body, signature.as_deref()).await {
+                Ok(status) if status.is_success() => return Ok(()),
+                Ok(status)
+                    if status.is_server_error()
+                        || status == http::StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    anyhow!("HTTP {}", status)
+                }
+                Ok(status) => bail!("Rejected with HTTP {}", status),
+                Err(e) => e,
+            };
+            if attempts >= self.retries {
+                return Err(error.context(format!("Gave up after {} attempts", attempts + 1)));
+            }
+            attempts += 1;
+            let delay = INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempts - 1));
+            tracing::debug!(
+                "{} failed ({:#}), retrying in {:?}",
+                self.name(),
+                error,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn send(
+        &self,
+        event: NotifyEvent,
+        body: &[u8],
+        signature: Option<&str>,
+    ) -> Result<http::StatusCode> {
+        let event = serde_json::to_value(event)?;
+        let mut request = http::Request::post(self.url.clone())
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.as_str().unwrap_or_default());
+        if let Some(signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        let request = request.body(Full::new(Bytes::copy_from_slice(body)))?;
+
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request))
+            .await
+            .context("Timed out")?
+            .context("Could not reach the webhook")?;
+        Ok(response.status())
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    /// Just the host, since webhook URLs often carry a token
+    fn name(&self) -> String {
+        format!("Webhook to {}", self.url.host().unwrap_or_default())
+    }
+
+    async fn session_started(&self, session: &Session) -> Result<()> {
+        let update = SessionUpdate::new(session.clone(), true);
+        self.post(NotifyEvent::SessionStart, &update).await
+    }
+
+    async fn status_changed(&self, update: &SessionUpdate) -> Result<()> {
+        self.post(NotifyEvent::StatusChange, update).await
+    }
+
+    async fn session_ended(&self, update: &SessionUpdate) -> Result<()> {
+        self.post(NotifyEvent::SessionEnd, update).await
+    }
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of the body
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::PAYLOAD_VERSION;
+    use http_body_util::BodyExt;
+    use hyper::body::Incoming;
+    use hyper::{Request, Response, StatusCode};
+    use hyper_util::rt::TokioIo;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    type Received = Arc<Mutex<Vec<(http::HeaderMap, Bytes)>>>;
+
+    /// Record every request, answering with `statuses` in turn and 200 after that
+    async fn listen(statuses: Vec<StatusCode>) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Received::default();
+        let statuses = Arc::new(Mutex::new(statuses.into_iter()));
+
+        let recorder = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (received, statuses) = (recorder.clone(), statuses.clone());
+                let service = hyper::service::service_fn(move |request: Request<Incoming>| {
+                    let (received, statuses) = (received.clone(), statuses.clone());
+                    async move {
+                        let (parts, body) = request.into_parts();
+                        let body = body.collect().await?.to_bytes();
+                        received.lock().unwrap().push((parts.headers, body));
+                        let status = statuses.lock().unwrap().next().unwrap_or(StatusCode::OK);
+                        let mut response = Response::new(Full::new(Bytes::new()));
+                        *response.status_mut() = status;
+                        Ok::<_, hyper::Error>(response)
+                    }
+                });
+                tokio::spawn(
+                    hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service),
+                );
+            }
+        });
+        (url, received)
+    }
+
+    fn notifier(url: String, retries: u32) -> WebhookNotifier {
+        WebhookNotifier::new(&WebhookConfig {
+            url,
+            secret: Some("shh".to_string()),
+            events: Vec::new(),
+            retries,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_webhook() {
+        let session = Session::new(PathBuf::from("/tmp/repo"), None, "test".to_string());
+
+        // A server error is retried, and the delivered payload is signed
+        let (url, received) = listen(vec![StatusCode::BAD_GATEWAY]).await;
+        notifier(url, 1).session_started(&session).await.unwrap();
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        let (headers, body) = &received[1];
+        assert_eq!(headers[SIGNATURE_HEADER], sign("shh", body));
+        assert_eq!(headers[EVENT_HEADER], "session_start");
+        let payload: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload["version"], PAYLOAD_VERSION);
+        assert_eq!(payload["event"], "session_start");
+        assert_eq!(payload["status_changed"], true);
+        assert_eq!(payload["session"]["id"], session.id.to_string());
+
+        // Other client errors are not
+        let (url, received) = listen(vec![StatusCode::NOT_FOUND]).await;
+        let update = SessionUpdate::new(session, true);
+        assert!(notifier(url, 3).session_ended(&update).await.is_err());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,376 @@
+//! A stand-in for the Slack Web API that records every call it receives.
+//!
+//! Point `SlackConfig::api_url` at [`MockSlack::api_url`] and assert on
+//! [`MockSlack::calls`]. Responses carry just enough for the daemon to carry on.
+//! File uploads sent to the URL from `files.getUploadURLExternal` are recorded
+//! as `upload` calls holding the file contents.
+
+#![allow(dead_code)]
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+/// One recorded Web API call
+#[derive(Debug, Clone)]
+pub struct SlackCall {
+    /// API method, e.g. `chat.postMessage`
+    pub method: String,
+    /// JSON body, or the query string as an object for GET calls
+    pub body: Value,
+}
+
+/// A canned response returned instead of the default one
+#[derive(Debug, Clone)]
+pub struct CannedResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Value,
+}
+
+impl CannedResponse {
+    /// HTTP 429 asking the client to wait
+    pub fn rate_limited(retry_after_secs: u64) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            headers: vec![("retry-after", retry_after_secs.to_string())],
+            body: json!({ "ok": false, "error": "ratelimited" }),
+        }
+    }
+
+    /// A successful answer with this body
+    pub fn ok(body: Value) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Add a response header
+    pub fn with_header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    /// `ok: false` with the given Slack error code
+    pub fn error(code: &str) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body: json!({ "ok": false, "error": code }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    calls: Mutex<Vec<SlackCall>>,
+    overrides: Mutex<HashMap<String, VecDeque<CannedResponse>>>,
+    delays: Mutex<HashMap<String, VecDeque<Duration>>>,
+    next_ts: AtomicU64,
+    changed: Notify,
+    /// Where files.getUploadURLExternal sends uploads
+    upload_url: OnceLock<String>,
+}
+
+/// A running mock Slack Web API
+pub struct MockSlack {
+    addr: std::net::SocketAddr,
+    state: Arc<State>,
+}
+
+impl MockSlack {
+    /// Listen on a free local port
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(State::default());
+        state
+            .upload_url
+            .set(format!("http://{}/upload", addr))
+            .unwrap();
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    break;
+                };
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(move |request| {
+                        let state = state.clone();
+                        async move { Ok::<_, hyper::Error>(handle(&state, request).await) }
+                    });
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self { addr, state }
+    }
+
+    /// Base URL for `SlackConfig::api_url`
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+
+    /// Answer the next calls to `method` with these responses, in order
+    pub fn respond_with(&self, method: &str, responses: impl IntoIterator<Item = CannedResponse>) {
+        self.state
+            .overrides
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .extend(responses);
+    }
+
+    /// Hold back the answers to the next calls to `method` this long, in order
+    pub fn delay(&self, method: &str, delays: impl IntoIterator<Item = Duration>) {
+        self.state
+            .delays
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .extend(delays);
+    }
+
+    /// Every call received so far
+    pub fn calls(&self) -> Vec<SlackCall> {
+        self.state.calls.lock().unwrap().clone()
+    }
+
+    /// Calls to one method
+    pub fn calls_to(&self, method: &str) -> Vec<SlackCall> {
+        self.calls()
+            .into_iter()
+            .filter(|c| c.method == method)
+            .collect()
+    }
+
+    /// Wait until `count` calls to `method` have arrived, failing after `timeout`
+    pub async fn wait_for(&self, method: &str, count: usize, timeout: Duration) -> Vec<SlackCall> {
+        self.wait_until(timeout, || {
+            let calls = self.calls_to(method);
+            (calls.len() >= count).then_some(calls)
+        })
+        .await
+        .unwrap_or_else(|| {
+            panic!(
+                "expected {} {} calls, got: {:#?}",
+                count,
+                method,
+                self.calls()
+            )
+        })
+    }
+
+    /// Wait for a call to `method` whose body matches, failing after `timeout`
+    pub async fn wait_for_match(
+        &self,
+        method: &str,
+        matches: impl Fn(&Value) -> bool,
+        timeout: Duration,
+    ) -> SlackCall {
+        self.wait_until(timeout, || {
+            self.calls_to(method).into_iter().find(|c| matches(&c.body))
+        })
+        .await
+        .unwrap_or_else(|| panic!("no matching {} call, got: {:#?}", method, self.calls()))
+    }
+
+    async fn wait_until<T>(&self, timeout: Duration, check: impl Fn() -> Option<T>) -> Option<T> {
+        let wait = async {
+            loop {
+                let changed = self.state.changed.notified();
+                if let Some(found) = check() {
+                    return found;
+                }
+                changed.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+}
+
+async fn handle(state: &State, request: Request<Incoming>) -> Response<Full<Bytes>> {
+    let method = request.uri().path().trim_start_matches("/api/").to_string();
+    let method = method.trim_start_matches('/').to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+    let bytes = request.into_body().collect().await.unwrap().to_bytes();
+
+    let body = if method == "upload" {
+        // Raw file contents
+        Value::String(String::from_utf8_lossy(&bytes).into_owned())
+    } else if bytes.is_empty() {
+        Value::Object(
+            url_pairs(&query)
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect(),
+        )
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or_else(|_| {
+            Value::Object(
+                url_pairs(&String::from_utf8_lossy(&bytes))
+                    .map(|(k, v)| (k, Value::String(v)))
+                    .collect(),
+            )
+        })
+    };
+
+    let delay = state
+        .delays
+        .lock()
+        .unwrap()
+        .get_mut(&method)
+        .and_then(|queue| queue.pop_front());
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let canned = state
+        .overrides
+        .lock()
+        .unwrap()
+        .get_mut(&method)
+        .and_then(|queue| queue.pop_front());
+
+    let response = match canned {
+        Some(canned) => canned,
+        None => CannedResponse {
+            status: StatusCode::OK,
+            headers: default_headers(&method),
+            body: default_response(state, &method, &body),
+        },
+    };
+
+    state.calls.lock().unwrap().push(SlackCall { method, body });
+    state.changed.notify_waiters();
+
+    let mut builder = Response::builder()
+        .status(response.status)
+        .header("content-type", "application/json");
+    for (name, value) in response.headers {
+        builder = builder.header(name, value);
+    }
+    builder
+        .body(Full::new(Bytes::from(response.body.to_string())))
+        .unwrap()
+}
+
+/// Headers Slack sends along with some answers
+fn default_headers(method: &str) -> Vec<(&'static str, String)> {
+    match method {
+        "auth.test" => vec![(
+            "x-oauth-scopes",
+            "chat:write,im:write,users:read,reactions:write".to_string(),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Successful answers shaped like Slack's
+fn default_response(state: &State, method: &str, body: &Value) -> Value {
+    let channel = body["channel"].as_str().unwrap_or("C0MOCK").to_string();
+    match method {
+        "conversations.open" => json!({ "ok": true, "channel": { "id": "D0MOCK" } }),
+        "auth.test" => json!({
+            "ok": true,
+            "url": "https://mock.slack.com/",
+            "team": "Mock Team",
+            "user": "slack-code",
+            "team_id": "T0MOCK",
+            "user_id": "U0BOT",
+            "bot_id": "B0MOCK"
+        }),
+        "users.lookupByEmail" => json!({
+            "ok": true,
+            "user": {
+                "id": "U0OWNER",
+                "name": "owner",
+                "profile": { "display_name": "olive", "email": body["email"] }
+            }
+        }),
+        "users.info" => json!({
+            "ok": true,
+            "user": {
+                "id": body["user"],
+                "name": "owner",
+                "real_name": "Olive Owner",
+                "profile": { "display_name": "olive", "real_name": "Olive Owner" }
+            }
+        }),
+        "chat.postMessage" => {
+            let n = state.next_ts.fetch_add(1, Ordering::SeqCst) + 1;
+            let ts = format!("1700000000.{:06}", n);
+            json!({
+                "ok": true,
+                "channel": channel,
+                "ts": ts,
+                "message": { "ts": ts, "text": body["text"] }
+            })
+        }
+        "chat.update" => json!({
+            "ok": true,
+            "channel": channel,
+            "ts": body["ts"],
+            "message": { "text": body["text"] }
+        }),
+        "files.getUploadURLExternal" => json!({
+            "ok": true,
+            "upload_url": state.upload_url.get().unwrap(),
+            "file_id": "F0MOCK"
+        }),
+        "chat.getPermalink" => json!({
+            "ok": true,
+            "channel": channel,
+            "permalink": format!("https://mock.slack.com/archives/{}/p1", channel)
+        }),
+        _ => json!({ "ok": true }),
+    }
+}
+
+/// Decode `a=1&b=2` pairs
+fn url_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query.split('&').filter(|p| !p.is_empty()).map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        (percent_decode(k), percent_decode(v))
+    })
+}
+
+/// Undo form encoding: `+` for spaces and `%XX` escapes
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
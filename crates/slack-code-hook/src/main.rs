@@ -1,5 +1,8 @@
 use anyhow::Result;
-use slack_code_common::ipc::{ClaudeHookInput, HookEvent};
+use slack_code_common::ipc::{
+    ClaudeHookInput, HookEvent, HookResponse, PermissionDecision, SESSION_ID_ENV,
+};
+use slack_code_common::{process, Config};
 use std::io::{self, Read, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
@@ -23,23 +26,102 @@ fn run() -> Result<()> {
     let hook_input: ClaudeHookInput = serde_json::from_str(&input)?;
 
     // Convert to our event type
-    let Some(event) = hook_input.to_hook_event() else {
+    let Some(mut event) = hook_input.to_hook_event() else {
         // Unknown event type, silently ignore
         return Ok(());
     };
 
+    if let HookEvent::SessionStart {
+        launch_id,
+        pid,
+        pid_started,
+        ..
+    } = &mut event
+    {
+        // Sessions started from Slack tell us which session they belong to
+        *launch_id = std::env::var(SESSION_ID_ENV).ok().and_then(|id| id.parse().ok());
+        // Claude Code runs the hook, so its PID lets the session be stopped later
+        let claude_pid = process::claude_pid();
+        *pid = Some(claude_pid);
+        *pid_started = process::start_time(claude_pid);
+    }
+
+    let config = Config::load().ok();
+
+    // Wait for an answer from Slack where the user enabled it
+    if let Some(interaction) = config.as_ref().map(|c| &c.interaction) {
+        match &mut event {
+            HookEvent::Stop { reply_timeout, .. } if interaction.thread_replies => {
+                *reply_timeout = Some(interaction.reply_timeout);
+            }
+            HookEvent::PermissionRequest {
+                decision_timeout, ..
+            } if interaction.permission_buttons => {
+                *decision_timeout = Some(interaction.permission_timeout);
+            }
+            _ => {}
+        }
+    }
+
     // Try to send to daemon
-    if let Err(e) = send_to_daemon(&event) {
-        // Daemon might not be running - that's okay
-        eprintln!("Could not notify daemon: {}", e);
+    match send_to_daemon(&event, get_socket_path(config.as_ref())) {
+        Ok(Some(HookResponse::Reply(prompt))) => {
+            // Block the stop so Claude continues with the reply as its next instruction
+            let output = serde_json::json!({
+                "decision": "block",
+                "reason": prompt,
+            });
+            println!("{}", output);
+        }
+        Ok(Some(HookResponse::Permission(decision))) => {
+            if let Some(output) = permission_output(&hook_input.hook_event_name, decision) {
+                println!("{}", output);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            // Daemon might not be running - that's okay
+            eprintln!("Could not notify daemon: {}", e);
+        }
     }
 
     Ok(())
 }
 
-fn send_to_daemon(event: &HookEvent) -> Result<()> {
-    let socket_path = get_socket_path();
+/// Hook output for a permission decision, or None to show the usual dialog
+fn permission_output(hook_event_name: &str, decision: PermissionDecision) -> Option<serde_json::Value> {
+    let (behavior, message) = match decision {
+        PermissionDecision::Allow => ("allow", "Approved from Slack"),
+        PermissionDecision::Deny => ("deny", "Denied from Slack"),
+        PermissionDecision::Ask => return None,
+    };
+
+    let output = if hook_event_name == "PreToolUse" {
+        serde_json::json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": behavior,
+                "permissionDecisionReason": message,
+            }
+        })
+    } else {
+        let mut decision = serde_json::json!({ "behavior": behavior });
+        if behavior == "deny" {
+            decision["message"] = message.into();
+        }
+        serde_json::json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PermissionRequest",
+                "decision": decision,
+            }
+        })
+    };
+
+    Some(output)
+}
 
+/// Send an event to the daemon, waiting for its response if the event expects one
+fn send_to_daemon(event: &HookEvent, socket_path: PathBuf) -> Result<Option<HookResponse>> {
     // Connect with timeout
     let stream = UnixStream::connect(&socket_path)?;
     stream.set_write_timeout(Some(Duration::from_secs(2)))?;
@@ -47,7 +129,15 @@ fn send_to_daemon(event: &HookEvent) -> Result<()> {
 
     send_message(&stream, event)?;
 
-    Ok(())
+    let Some(timeout) = event.response_timeout() else {
+        return Ok(None);
+    };
+
+    // Leave the daemon room to answer with its own timeout fallback
+    stream.set_read_timeout(Some(timeout + Duration::from_secs(5)))?;
+    let response = read_message(&stream)?;
+
+    Ok(Some(serde_json::from_str(&response)?))
 }
 
 fn send_message<T: serde::Serialize>(mut stream: &UnixStream, msg: &T) -> Result<()> {
@@ -61,10 +151,21 @@ fn send_message<T: serde::Serialize>(mut stream: &UnixStream, msg: &T) -> Result
     Ok(())
 }
 
-fn get_socket_path() -> PathBuf {
-    // Try to load from config, fallback to default
-    if let Ok(config) = slack_code_common::Config::load() {
-        return config.daemon.socket_path;
+fn read_message(mut stream: &UnixStream) -> Result<String> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+
+    Ok(String::from_utf8(buf)?)
+}
+
+fn get_socket_path(config: Option<&Config>) -> PathBuf {
+    // Use the configured path, fallback to default
+    if let Some(config) = config {
+        return config.daemon.socket_path.clone();
     }
 
     // Default path
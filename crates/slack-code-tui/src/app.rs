@@ -6,13 +6,15 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 use ratatui::widgets::ListState;
-use slack_code_common::ipc::DaemonEvent;
+use slack_code_common::ipc::{DaemonCommand, DaemonEvent, DaemonStatus};
 use slack_code_common::session::Session;
 use slack_code_common::Config;
+use slack_code_daemon::check::{check_slack, Check, Outcome};
 use slack_code_daemon::ipc::{EventSubscription, IpcClient};
 use std::collections::VecDeque;
 use std::io;
 use std::time::Duration;
+use tokio::sync::oneshot;
 
 use crate::events::{EventHandler, InputEvent, Message};
 use crate::ui;
@@ -60,6 +62,15 @@ pub struct App {
     /// Whether daemon is connected
     daemon_connected: bool,
 
+    /// Daemon's Socket Mode connection to Slack (if reported)
+    slack_status: Option<DaemonStatus>,
+
+    /// Slack check started with "Test tokens", until its results are in
+    token_check: Option<oneshot::Receiver<Vec<Check>>>,
+
+    /// Connection for sending commands to the daemon
+    ipc_client: IpcClient,
+
     /// Should quit
     should_quit: bool,
 
@@ -73,6 +84,7 @@ impl App {
         let config = Config::load().unwrap_or_default();
         let hooks_installed =
             slack_code_common::config::check_hooks_installed().unwrap_or(false);
+        let ipc_client = IpcClient::new(config.daemon.socket_path.clone());
 
         Ok(Self {
             mode: AppMode::Sessions,
@@ -85,6 +97,9 @@ impl App {
             log_scroll: 0,
             hooks_installed,
             daemon_connected: false,
+            slack_status: None,
+            token_check: None,
+            ipc_client,
             should_quit: false,
             list_state: ListState::default(),
         })
@@ -111,6 +126,8 @@ impl App {
             Ok(sub) => {
                 app.daemon_connected = true;
                 app.add_log(LogEntry::info("Connected to daemon"));
+                // Ask for the current Slack connection state
+                let _ = ipc_client.send_command(&DaemonCommand::Ping);
                 Some(sub)
             }
             Err(e) => {
@@ -166,11 +183,13 @@ impl App {
                     // Terminal will redraw automatically
                 }
                 InputEvent::Tick => {
+                    self.poll_token_check();
+
                     // Poll daemon for events (non-blocking)
                     if let Some(ref mut sub) = daemon_sub {
                         match sub.try_recv() {
                             Ok(Some(event)) => {
-                                self.update(Message::DaemonEvent(event));
+                                self.update(Message::DaemonEvent(Box::new(event)));
                             }
                             Ok(None) => {} // No event ready
                             Err(_) => {
@@ -218,13 +237,19 @@ impl App {
                 self.handle_select();
             }
             Message::Escape => {
-                if self.show_help {
-                    self.show_help = false;
-                }
+                self.show_help = false;
             }
             Message::TestTokens => {
+                if self.token_check.is_some() {
+                    return;
+                }
                 self.add_log(LogEntry::info("Testing Slack tokens..."));
-                // TODO: Actually test tokens
+                let (tx, rx) = oneshot::channel();
+                let slack = self.config.slack.clone();
+                tokio::spawn(async move {
+                    let _ = tx.send(check_slack(&slack).await);
+                });
+                self.token_check = Some(rx);
             }
             Message::ManageHooks => {
                 if self.hooks_installed {
@@ -259,24 +284,44 @@ impl App {
                 self.add_log(LogEntry::info("Refreshing sessions..."));
                 // TODO: Request sessions from daemon
             }
+            Message::StopSession => {
+                let Some(session) = self.sessions.get(self.selected_index) else {
+                    return;
+                };
+                if !session.is_active() {
+                    self.add_log(LogEntry::warning(format!(
+                        "{} has already ended",
+                        session.display_name()
+                    )));
+                    return;
+                }
+                let name = session.display_name();
+                match self
+                    .ipc_client
+                    .send_command(&DaemonCommand::StopSession(session.id))
+                {
+                    Ok(()) => self.add_log(LogEntry::info(format!("Stopping {}...", name))),
+                    Err(e) => self.add_log(LogEntry::error(format!(
+                        "Failed to stop {}: {}",
+                        name, e
+                    ))),
+                }
+            }
             Message::DaemonEvent(event) => {
-                self.handle_daemon_event(event);
+                self.handle_daemon_event(*event);
             }
             _ => {}
         }
     }
 
     fn handle_select(&mut self) {
-        match &self.mode {
-            AppMode::Config => {
-                // Cycle through config sections
-                self.config_section = match self.config_section {
-                    ConfigSection::SlackTokens => ConfigSection::Hooks,
-                    ConfigSection::Hooks => ConfigSection::SlackTokens,
-                };
-                self.selected_index = 0;
-            }
-            _ => {}
+        if self.mode == AppMode::Config {
+            // Cycle through config sections
+            self.config_section = match self.config_section {
+                ConfigSection::SlackTokens => ConfigSection::Hooks,
+                ConfigSection::Hooks => ConfigSection::SlackTokens,
+            };
+            self.selected_index = 0;
         }
     }
 
@@ -285,9 +330,9 @@ impl App {
             DaemonEvent::SessionUpdated(session) => {
                 // Update or add session
                 if let Some(existing) = self.sessions.iter_mut().find(|s| s.id == session.id) {
-                    *existing = session;
+                    *existing = *session;
                 } else {
-                    self.sessions.push(session);
+                    self.sessions.push(*session);
                 }
             }
             DaemonEvent::SessionRemoved(id) => {
@@ -297,19 +342,56 @@ impl App {
                 self.add_log(LogEntry::error(msg));
             }
             DaemonEvent::Status(status) => {
-                self.daemon_connected =
-                    matches!(status, slack_code_common::ipc::DaemonStatus::Connected);
+                if let DaemonStatus::Disconnected(reason) = &status {
+                    self.add_log(LogEntry::warning(format!(
+                        "Slack Socket Mode disconnected: {}",
+                        reason
+                    )));
+                }
+                self.slack_status = Some(status);
             }
             DaemonEvent::SessionList(sessions) => {
                 self.sessions = sessions;
             }
             DaemonEvent::ConfigResponse(config) => {
-                self.config = config;
+                self.config = *config;
             }
             _ => {}
         }
     }
 
+    /// Log the results of a finished token check
+    fn poll_token_check(&mut self) {
+        let Some(ref mut rx) = self.token_check else {
+            return;
+        };
+        let checks = match rx.try_recv() {
+            Ok(checks) => checks,
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.token_check = None;
+                self.add_log(LogEntry::error("Token check stopped unexpectedly"));
+                return;
+            }
+        };
+        self.token_check = None;
+
+        let failed = checks.iter().any(|c| c.failed());
+        for check in checks {
+            let entry = match check.outcome {
+                Outcome::Passed(_) => LogEntry::info(check.to_string()),
+                Outcome::Skipped(_) => LogEntry::warning(check.to_string()),
+                Outcome::Failed(_) => LogEntry::error(check.to_string()),
+            };
+            self.add_log(entry);
+        }
+        if failed {
+            self.add_log(LogEntry::error("Slack is not set up correctly"));
+        } else {
+            self.add_log(LogEntry::info("Slack tokens OK"));
+        }
+    }
+
     fn add_log(&mut self, entry: LogEntry) {
         self.logs.push_back(entry);
         if self.logs.len() > 1000 {
@@ -337,7 +419,12 @@ impl App {
             .split(frame.area());
 
         // Header
-        ui::render_header(frame, chunks[0], self.daemon_connected);
+        ui::render_header(
+            frame,
+            chunks[0],
+            self.daemon_connected,
+            self.slack_status.as_ref(),
+        );
 
         // Content based on mode
         match &self.mode {
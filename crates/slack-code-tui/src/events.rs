@@ -57,12 +57,13 @@ pub enum Message {
 
     // Session actions
     RefreshSessions,
+    StopSession,
 
     // Help
     ToggleHelp,
 
     // Daemon events
-    DaemonEvent(slack_code_common::ipc::DaemonEvent),
+    DaemonEvent(Box<slack_code_common::ipc::DaemonEvent>),
 }
 
 impl Message {
@@ -97,6 +98,9 @@ impl Message {
             KeyCode::Char('r') if matches!(mode, crate::app::AppMode::Sessions) => {
                 Some(Message::RefreshSessions)
             }
+            KeyCode::Char('s') if matches!(mode, crate::app::AppMode::Sessions) => {
+                Some(Message::StopSession)
+            }
 
             _ => None,
         }
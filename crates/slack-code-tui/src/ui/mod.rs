@@ -4,18 +4,32 @@ pub mod sessions;
 
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
+use slack_code_common::ipc::DaemonStatus;
 
 /// Render the header bar
-pub fn render_header(frame: &mut Frame, area: Rect, daemon_connected: bool) {
+pub fn render_header(
+    frame: &mut Frame,
+    area: Rect,
+    daemon_connected: bool,
+    slack_status: Option<&DaemonStatus>,
+) {
     let status = if daemon_connected {
         "Daemon: [Connected]"
     } else {
         "Daemon: [Disconnected]"
     };
 
-    let header_text = format!("  slack-code v{}                           {}",
+    let slack = match slack_status {
+        Some(DaemonStatus::Connected) => "Slack: [Connected]",
+        Some(DaemonStatus::Connecting) => "Slack: [Connecting]",
+        Some(DaemonStatus::Disconnected(_)) => "Slack: [Disconnected]",
+        None => "Slack: [Unknown]",
+    };
+
+    let header_text = format!("  slack-code v{}                           {}  {}",
         env!("CARGO_PKG_VERSION"),
-        status
+        status,
+        slack
     );
 
     let header = Paragraph::new(header_text)
@@ -29,7 +43,7 @@ pub fn render_header(frame: &mut Frame, area: Rect, daemon_connected: bool) {
 pub fn render_status_bar(frame: &mut Frame, area: Rect, mode: &crate::app::AppMode) {
     let keybindings = match mode {
         crate::app::AppMode::Sessions => {
-            "[1] Sessions  [2] Config  [3] Logs  [r] Refresh  [?] Help  [q] Quit"
+            "[1] Sessions  [2] Config  [3] Logs  [r] Refresh  [s] Stop  [?] Help  [q] Quit"
         }
         crate::app::AppMode::Config => {
             "[t] Test tokens  [h] Manage hooks  [?] Help  [q] Quit"
@@ -69,6 +83,7 @@ pub fn render_help_overlay(frame: &mut Frame, area: Rect) {
 
     Sessions:
       r         Refresh sessions
+      s         Stop the selected session's agent
 
     Config:
       t         Test Slack tokens
@@ -1,5 +1,6 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "slack-code")]
@@ -12,8 +13,8 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Run the interactive setup wizard
-    Setup,
+    /// Run the interactive setup wizard, or set up from flags with --yes
+    Setup(SetupArgs),
 
     /// Manage the background daemon
     Daemon {
@@ -26,6 +27,41 @@ pub enum Commands {
         #[command(subcommand)]
         action: HooksAction,
     },
+
+    /// Check the Slack configuration
+    Slack {
+        #[command(subcommand)]
+        action: SlackAction,
+    },
+
+    /// Manage repository aliases
+    Repos {
+        #[command(subcommand)]
+        action: ReposAction,
+    },
+}
+
+#[derive(Args)]
+pub struct SetupArgs {
+    /// Environment variable holding the bot token [default: SLACK_CODE_BOT_TOKEN]
+    #[arg(long, value_name = "VAR", env = "SLACK_CODE_BOT_TOKEN_ENV")]
+    pub bot_token_env: Option<String>,
+
+    /// Environment variable holding the app token, if any [default: SLACK_CODE_APP_TOKEN]
+    #[arg(long, value_name = "VAR", env = "SLACK_CODE_APP_TOKEN_ENV")]
+    pub app_token_env: Option<String>,
+
+    /// Your Slack member ID (U...)
+    #[arg(long, env = "SLACK_CODE_USER_ID")]
+    pub user_id: Option<String>,
+
+    /// Install the Claude Code hooks as well
+    #[arg(long, env = "SLACK_CODE_INSTALL_HOOKS")]
+    pub install_hooks: bool,
+
+    /// Don't prompt; take every value from flags and environment variables
+    #[arg(long, short = 'y', env = "SLACK_CODE_SETUP_YES")]
+    pub yes: bool,
 }
 
 #[derive(Subcommand)]
@@ -48,6 +84,26 @@ pub enum HooksAction {
     Status,
 }
 
+#[derive(Subcommand)]
+pub enum SlackAction {
+    /// Verify the tokens, bot scopes, user ID and DM channel
+    Test,
+}
+
+#[derive(Subcommand)]
+pub enum ReposAction {
+    /// Register a repository under a short alias
+    Add {
+        alias: String,
+        /// Repository directory [default: the current directory]
+        path: Option<PathBuf>,
+    },
+    /// Forget a repository alias
+    Remove { alias: String },
+    /// List repository aliases
+    List,
+}
+
 pub async fn handle_daemon_command(action: DaemonAction) -> Result<()> {
     match action {
         DaemonAction::Start => {
@@ -80,13 +136,74 @@ pub async fn handle_hooks_command(action: HooksAction) -> Result<()> {
             if installed {
                 println!("Hooks are installed.");
             } else {
-                println!("Hooks are NOT installed.");
+                println!("Hooks are NOT installed, or some are missing.");
+                println!("Run 'slack-code hooks install' to install them.");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle_slack_command(action: SlackAction) -> Result<()> {
+    match action {
+        SlackAction::Test => {
+            let config = slack_code_common::Config::load()?;
+            let checks = slack_code_daemon::check::check_slack(&config.slack).await;
+            for check in &checks {
+                println!("{}", check);
+            }
+            if checks.iter().any(|c| c.failed()) {
+                anyhow::bail!("Slack is not set up correctly");
             }
+            println!("Slack is set up correctly.");
         }
     }
     Ok(())
 }
 
+pub async fn handle_repos_command(action: ReposAction) -> Result<()> {
+    let mut config = slack_code_common::Config::load()?;
+    match action {
+        ReposAction::Add { alias, path } => {
+            slack_code_common::config::validate_repo_alias(&alias)?;
+            let path = match path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            let path = path
+                .canonicalize()
+                .with_context(|| format!("{} does not exist", path.display()))?;
+            if !path.is_dir() {
+                anyhow::bail!("{} is not a directory", path.display());
+            }
+            println!("{} -> {}", alias, path.display());
+            config.repos.insert(alias, path);
+        }
+        ReposAction::Remove { alias } => {
+            if config.repos.remove(&alias).is_none() {
+                anyhow::bail!("No repository is called '{}'", alias);
+            }
+            println!("Removed {}.", alias);
+        }
+        ReposAction::List => {
+            if config.repos.is_empty() {
+                println!("No repositories registered, add one with 'slack-code repos add <alias>'.");
+            }
+            let width = config.repos.keys().map(|a| a.len()).max().unwrap_or(0);
+            for (alias, path) in &config.repos {
+                println!("{:width$}  {}", alias, path.display(), width = width);
+            }
+            return Ok(());
+        }
+    }
+
+    config.save()?;
+    if is_daemon_running().await {
+        println!("Restart the daemon to pick up the change.");
+    }
+    Ok(())
+}
+
 pub async fn start_tui() -> Result<()> {
     // Ensure daemon is running
     if !is_daemon_running().await {
@@ -30,8 +30,8 @@ fn main() -> Result<()> {
 
 async fn async_main(cli: Cli) -> Result<()> {
     match cli.command {
-        Some(Commands::Setup) => {
-            setup::run_setup_wizard().await?;
+        Some(Commands::Setup(args)) => {
+            setup::run_setup(args).await?;
         }
         Some(Commands::Daemon { action }) => {
             cli::handle_daemon_command(action).await?;
@@ -39,6 +39,12 @@ async fn async_main(cli: Cli) -> Result<()> {
         Some(Commands::Hooks { action }) => {
             cli::handle_hooks_command(action).await?;
         }
+        Some(Commands::Slack { action }) => {
+            cli::handle_slack_command(action).await?;
+        }
+        Some(Commands::Repos { action }) => {
+            cli::handle_repos_command(action).await?;
+        }
         None => {
             // Default: start TUI (which also starts daemon if needed)
             cli::start_tui().await?;
@@ -1,4 +1,7 @@
-use anyhow::Result;
+use crate::cli::SetupArgs;
+use anyhow::{bail, Context, Result};
+use slack_code_common::{Config, SlackCodeError};
+use slack_code_daemon::check;
 use std::io::{self, Write};
 
 /// Validate Slack Member ID format (U followed by alphanumeric, typically 8-11 chars)
@@ -10,13 +13,164 @@ fn validate_slack_user_id(id: &str) -> bool {
     rest.len() >= 8 && rest.chars().all(|c| c.is_ascii_alphanumeric())
 }
 
-pub async fn run_setup_wizard() -> Result<()> {
+/// Print a prompt and read one trimmed line
+fn prompt(text: &str) -> Result<String> {
+    print!("{}", text);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Run setup from flags and environment variables when `--yes` is given,
+/// otherwise interactively
+pub async fn run_setup(args: SetupArgs) -> Result<()> {
+    if args.yes {
+        run_unattended_setup(args).await
+    } else {
+        run_setup_wizard().await
+    }
+}
+
+/// Set up without prompts, failing on anything the wizard would ask about again
+async fn run_unattended_setup(args: SetupArgs) -> Result<()> {
+    let existing = existing_config()?;
+    // Check the tokens wherever the config points the daemon
+    let api_url = existing.as_ref().and_then(|c| c.slack.api_url.clone());
+    let token_var = args
+        .bot_token_env
+        .as_deref()
+        .unwrap_or("SLACK_CODE_BOT_TOKEN");
+    let bot_token = std::env::var(token_var).unwrap_or_default();
+    let bot_token = bot_token.trim().to_string();
+    if bot_token.is_empty() {
+        bail!(
+            "No bot token: set {} (or pass --bot-token-env with the variable holding it)",
+            token_var
+        );
+    }
+
+    let identity = check::auth_test(api_url.as_deref(), &bot_token)
+        .await
+        .context("Bot token check failed")?;
+    let missing = identity.missing_scopes();
+    if !missing.is_empty() {
+        bail!(
+            "The bot token is missing {}. Add them under OAuth & Permissions and reinstall the app.",
+            missing.join(", ")
+        );
+    }
+    println!("✓ Connected as @{} in {}", identity.user, identity.team);
+
+    let Some(user_id) = args.user_id.filter(|id| !id.is_empty()) else {
+        bail!("No member ID: pass --user-id or set SLACK_CODE_USER_ID");
+    };
+    if !validate_slack_user_id(&user_id) {
+        bail!(
+            "Invalid member ID '{}': member IDs start with 'U' followed by 8+ alphanumeric characters",
+            user_id
+        );
+    }
+    let user = check::user_info(api_url.as_deref(), &bot_token, &user_id)
+        .await
+        .context("Member ID check failed")?;
+    println!("✓ {} is {}", user_id, check::user_display_name(&user));
+
+    let app_var = args
+        .app_token_env
+        .as_deref()
+        .unwrap_or("SLACK_CODE_APP_TOKEN");
+    let app_token = std::env::var(app_var)
+        .map(|token| token.trim().to_string())
+        .ok()
+        .filter(|token| !token.is_empty());
+    if let Some(ref app_token) = app_token {
+        check::socket_mode_check(api_url.as_deref(), app_token)
+            .await
+            .context("App token check failed")?;
+        println!("✓ App token allows Socket Mode");
+    }
+
+    config_with_account(existing, bot_token, app_token, user_id).save()?;
+    println!("✓ Configuration saved to {}", Config::config_path().display());
+
+    if args.install_hooks {
+        slack_code_common::config::install_hooks().context("Failed to install hooks")?;
+        println!("✓ Hooks installed");
+    }
+
+    Ok(())
+}
+
+/// The configuration already on disk, if any
+fn existing_config() -> Result<Option<Config>> {
+    match Config::load() {
+        Ok(config) => Ok(Some(config)),
+        Err(SlackCodeError::ConfigNotFound) => Ok(None),
+        Err(e) => Err(e).with_context(|| {
+            format!(
+                "Could not read {}; fix or move it, then run setup again",
+                Config::config_path().display()
+            )
+        }),
+    }
+}
+
+/// Configuration written by setup: the Slack account in the existing file, or
+/// in defaults for everything else. A `None` app token keeps the current one.
+fn config_with_account(
+    existing: Option<Config>,
+    bot_token: String,
+    app_token: Option<String>,
+    user_id: String,
+) -> Config {
+    if let Some(mut config) = existing {
+        config.slack.bot_token = bot_token;
+        config.slack.user_id = user_id;
+        if let Some(app_token) = app_token {
+            config.slack.app_token = app_token;
+        }
+        return config;
+    }
+
+    Config {
+        slack: slack_code_common::config::SlackConfig {
+            bot_token,
+            // Not needed for notification-only mode
+            app_token: app_token.unwrap_or_default(),
+            user_id,
+            api_url: None,
+        },
+        daemon: slack_code_common::config::DaemonConfig::default(),
+        defaults: slack_code_common::config::DefaultsConfig::default(),
+        interaction: slack_code_common::config::InteractionConfig::default(),
+        notifications: slack_code_common::config::NotificationsConfig::default(),
+        transcript: slack_code_common::config::TranscriptConfig::default(),
+        templates: slack_code_common::config::TemplatesConfig::default(),
+        notifiers: slack_code_common::config::NotifiersConfig::default(),
+        routes: Vec::new(),
+        repos: Default::default(),
+        launch: slack_code_common::config::LaunchConfig::default(),
+    }
+}
+
+async fn run_setup_wizard() -> Result<()> {
+    let existing = existing_config()?;
+    // Check the tokens wherever the config points the daemon
+    let api_url = existing.as_ref().and_then(|c| c.slack.api_url.clone());
+
     println!();
     println!("╔════════════════════════════════════════════════════════════════╗");
     println!("║                    slack-code Setup Wizard                     ║");
     println!("╚════════════════════════════════════════════════════════════════╝");
     println!();
     println!("Welcome to slack-code! Let's get you set up.");
+    if existing.is_some() {
+        println!(
+            "Your Slack account in {} will be replaced; other settings are kept.",
+            Config::config_path().display()
+        );
+    }
     println!();
 
     // Step 1: Slack App Setup instructions
@@ -29,7 +183,8 @@ pub async fn run_setup_wizard() -> Result<()> {
     println!("3. Name it \"slack-code\" and select your workspace");
     println!("4. Add Bot Token Scopes:");
     println!("   - OAuth & Permissions → Scopes → Bot Token Scopes");
-    println!("   - Add: chat:write, im:write, users:read");
+    println!("   - Add: chat:write, im:write, users:read, reactions:write");
+    println!("   - Optional: users:read.email, to find your member ID by email");
     println!("5. Install to workspace and copy the Bot OAuth Token");
     println!();
     print!("Press Enter when ready...");
@@ -39,55 +194,140 @@ pub async fn run_setup_wizard() -> Result<()> {
 
     // Step 2: Enter tokens
     println!();
-    println!("Step 2/4: Enter Bot Token");
-    println!("─────────────────────────");
+    println!("Step 2/4: Enter Tokens");
+    println!("──────────────────────");
 
-    print!("Bot OAuth Token (xoxb-...): ");
-    io::stdout().flush()?;
-    let mut bot_token = String::new();
-    io::stdin().read_line(&mut bot_token)?;
-    let bot_token = bot_token.trim().to_string();
+    let bot_token = loop {
+        let bot_token = prompt("Bot OAuth Token (xoxb-...): ")?;
+        if !bot_token.starts_with("xoxb-") {
+            println!("Warning: Bot tokens typically start with 'xoxb-'");
+        }
 
-    if !bot_token.starts_with("xoxb-") {
-        println!("Warning: Bot tokens typically start with 'xoxb-'");
-    }
+        match check::auth_test(api_url.as_deref(), &bot_token).await {
+            Ok(identity) => {
+                println!("✓ Connected as @{} in {}", identity.user, identity.team);
+                let missing = identity.missing_scopes();
+                if !missing.is_empty() {
+                    println!(
+                        "Warning: the token is missing {}. Add them under OAuth & Permissions and reinstall the app.",
+                        missing.join(", ")
+                    );
+                }
+                break bot_token;
+            }
+            Err(e) => {
+                println!("✗ {:#}", e);
+                println!("Please check the token and try again.");
+            }
+        }
+    };
 
+    // Socket Mode is optional, and an existing app token can be kept
+    let current_app_token = existing
+        .as_ref()
+        .map(|config| config.slack.app_token.clone())
+        .filter(|token| !token.is_empty());
     println!();
-    println!("✓ Token saved!");
+    println!("Replies and commands from Slack need an App-Level Token for Socket Mode.");
+    let app_token = loop {
+        let app_token = if current_app_token.is_some() {
+            prompt("App-Level Token (xapp-..., leave empty to keep the current one): ")?
+        } else {
+            prompt("App-Level Token (xapp-..., leave empty for notifications only): ")?
+        };
+        if app_token.is_empty() {
+            break None;
+        }
+        if !app_token.starts_with("xapp-") {
+            println!("Warning: App-Level Tokens typically start with 'xapp-'");
+        }
+
+        match check::socket_mode_check(api_url.as_deref(), &app_token).await {
+            Ok(()) => {
+                println!("✓ App token allows Socket Mode");
+                break Some(app_token);
+            }
+            Err(e) => {
+                println!("✗ {:#}", e);
+                println!("Please check the token and try again.");
+            }
+        }
+    };
 
     // Step 3: Slack Member ID
     println!();
-    println!("Step 3/4: Enter Your Slack Member ID");
-    println!("────────────────────────────────────");
+    println!("Step 3/4: Find Your Slack Account");
+    println!("─────────────────────────────────");
     println!("To send you DM notifications, we need your Slack Member ID.");
     println!();
-    println!("To find your Member ID:");
-    println!("1. Open Slack and click on your profile picture");
-    println!("2. Click 'Profile'");
-    println!("3. Click the three dots (...) menu");
-    println!("4. Select 'Copy member ID'");
-    println!();
 
-    let user_id = loop {
-        print!("Slack Member ID (U...): ");
-        io::stdout().flush()?;
-        let mut user_id_input = String::new();
-        io::stdin().read_line(&mut user_id_input)?;
-        let user_id_input = user_id_input.trim();
-
-        if user_id_input.is_empty() {
-            println!("Member ID is required. Please enter your Slack Member ID.");
-            continue;
+    let mut user_id = None;
+    loop {
+        let email = prompt("Your Slack email (leave empty to enter your Member ID instead): ")?;
+        if email.is_empty() {
+            break;
         }
+        match check::lookup_user_by_email(api_url.as_deref(), &bot_token, &email).await {
+            Ok(user) => {
+                let answer = prompt(&format!(
+                    "Found {} ({}). Is this you? (y/n): ",
+                    check::user_display_name(&user),
+                    user.id
+                ))?;
+                if answer.to_lowercase() == "y" {
+                    user_id = Some(user.id.to_string());
+                    break;
+                }
+            }
+            Err(e) if check::slack_error_code(&e) == Some("users_not_found") => {
+                println!("No Slack account uses that email. Try another one.");
+            }
+            Err(e) if check::slack_error_code(&e) == Some("missing_scope") => {
+                println!("Looking up by email needs the users:read.email scope.");
+                break;
+            }
+            Err(e) => {
+                println!("✗ {:#}", e);
+                break;
+            }
+        }
+    }
 
-        if validate_slack_user_id(user_id_input) {
-            println!("✓ Member ID saved!");
-            break user_id_input.to_string();
-        } else {
-            println!("Invalid format. Member IDs start with 'U' followed by 8+ alphanumeric characters.");
-            println!("Example: U01ABC123DE");
+    let user_id = match user_id {
+        Some(user_id) => user_id,
+        None => {
+            println!();
+            println!("To find your Member ID:");
+            println!("1. Open Slack and click on your profile picture");
+            println!("2. Click 'Profile'");
+            println!("3. Click the three dots (...) menu");
+            println!("4. Select 'Copy member ID'");
+            println!();
+
+            loop {
+                let user_id = prompt("Slack Member ID (U...): ")?;
+
+                if user_id.is_empty() {
+                    println!("Member ID is required. Please enter your Slack Member ID.");
+                    continue;
+                }
+                if !validate_slack_user_id(&user_id) {
+                    println!("Invalid format. Member IDs start with 'U' followed by 8+ alphanumeric characters.");
+                    println!("Example: U01ABC123DE");
+                    continue;
+                }
+
+                match check::user_info(api_url.as_deref(), &bot_token, &user_id).await {
+                    Ok(user) => {
+                        println!("✓ Found {}", check::user_display_name(&user));
+                        break user_id;
+                    }
+                    Err(e) => println!("✗ {:#}", e),
+                }
+            }
         }
     };
+    println!("✓ Member ID saved!");
 
     // Step 4: Install hooks
     println!();
@@ -118,17 +358,7 @@ pub async fn run_setup_wizard() -> Result<()> {
     };
 
     // Save configuration
-    let config = slack_code_common::Config {
-        slack: slack_code_common::config::SlackConfig {
-            bot_token,
-            app_token: String::new(), // Not needed for notification-only mode
-            user_id,
-        },
-        daemon: slack_code_common::config::DaemonConfig::default(),
-        defaults: slack_code_common::config::DefaultsConfig::default(),
-    };
-
-    config.save()?;
+    config_with_account(existing, bot_token, app_token, user_id).save()?;
 
     // Done
     println!();